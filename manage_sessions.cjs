@@ -28,6 +28,11 @@ console.log(`Using database: ${dbPath}\n`);
 
 const db = new sqlite3.Database(dbPath);
 
+// Store timestamps in UTC the way the app writes them, so they compare as text
+function toStoredTime(date) {
+  return date.toISOString().replace(/\.\d{3}Z$/, '+00:00');
+}
+
 function listSessions() {
   db.all('SELECT * FROM sessions ORDER BY start DESC', [], (err, rows) => {
     if (err) {
@@ -69,16 +74,14 @@ function addSession(startStr, endStr) {
   
   const session = {
     id,
-    start: start.toISOString(),
-    pauses: '[]',
-    resumes: '[]',
-    end: end.toISOString(),
+    start: toStoredTime(start),
+    end: toStoredTime(end),
     total_seconds: totalSeconds
   };
   
   db.run(
-    'INSERT INTO sessions (id, start, pauses, resumes, end, total_seconds) VALUES (?, ?, ?, ?, ?, ?)',
-    [session.id, session.start, session.pauses, session.resumes, session.end, session.total_seconds],
+    'INSERT INTO sessions (id, start, end, total_seconds) VALUES (?, ?, ?, ?)',
+    [session.id, session.start, session.end, session.total_seconds],
     function(err) {
       if (err) {
         console.error('Error:', err);
@@ -121,7 +124,7 @@ function clearAllSessions() {
 function clearExceptToday() {
   const today = new Date();
   today.setHours(0, 0, 0, 0);
-  const todayStr = today.toISOString();
+  const todayStr = toStoredTime(today);
   
   db.run('DELETE FROM sessions WHERE start < ?', [todayStr], function(err) {
     if (err) {
@@ -45,21 +45,22 @@ case "$1" in
     END="$3"
     ID=$(uuidgen | tr '[:upper:]' '[:lower:]')
     
-    # Convert local time to UTC ISO format (accounting for timezone)
-    START_UTC=$(date -j -f "%Y-%m-%d %H:%M" "$START" "+%Y-%m-%dT%H:%M:%S%z" 2>/dev/null | sed 's/\([+-][0-9][0-9]\)\([0-9][0-9]\)$/\1:\2/')
-    END_UTC=$(date -j -f "%Y-%m-%d %H:%M" "$END" "+%Y-%m-%dT%H:%M:%S%z" 2>/dev/null | sed 's/\([+-][0-9][0-9]\)\([0-9][0-9]\)$/\1:\2/')
+    # Read the local times, then store them in UTC like the app does
+    START_EPOCH=$(date -j -f "%Y-%m-%d %H:%M" "$START" "+%s" 2>/dev/null)
+    END_EPOCH=$(date -j -f "%Y-%m-%d %H:%M" "$END" "+%s" 2>/dev/null)
     
-    if [ -z "$START_UTC" ] || [ -z "$END_UTC" ]; then
+    if [ -z "$START_EPOCH" ] || [ -z "$END_EPOCH" ]; then
       echo "Error: Invalid date format. Use: YYYY-MM-DD HH:MM"
       exit 1
     fi
     
+    START_UTC=$(date -u -r "$START_EPOCH" "+%Y-%m-%dT%H:%M:%S+00:00")
+    END_UTC=$(date -u -r "$END_EPOCH" "+%Y-%m-%dT%H:%M:%S+00:00")
+    
     # Calculate duration in seconds
-    START_EPOCH=$(date -j -f "%Y-%m-%d %H:%M" "$START" "+%s")
-    END_EPOCH=$(date -j -f "%Y-%m-%d %H:%M" "$END" "+%s")
     DURATION=$((END_EPOCH - START_EPOCH))
     
-    sqlite3 "$DB_PATH" "INSERT INTO sessions (id, start, pauses, resumes, end, total_seconds) VALUES ('$ID', '$START_UTC', '[]', '[]', '$END_UTC', $DURATION)"
+    sqlite3 "$DB_PATH" "INSERT INTO sessions (id, start, end, total_seconds) VALUES ('$ID', '$START_UTC', '$END_UTC', $DURATION)"
     
     echo "✓ Session added successfully!"
     echo "  ID: $ID"
@@ -1,8 +1,14 @@
+use crate::error::{OrError, TimelyError};
 use crate::excel_exporter::ExcelExporter;
-use crate::models::{MonthlySummary, Session, TimerState};
+use crate::models::{
+    Break, Client, GapResolution, Granularity, HotkeyConflict, HotkeySettings, IdleInterval,
+    IdleResolution, IdleSettings, IntegrityReport, MonthlyExport, MonthlySummary, OverlapMode,
+    PomodoroSettings, PomodoroState, Project, Rows, RunningTimer, Session, SessionBudget,
+    SessionGap, SessionPage, SessionQuery, Summary, SummaryRange, TimerState, WorkRules,
+};
 use crate::session_store::SessionStore;
 use crate::timer_manager::TimerManager;
-use chrono::{Datelike, Utc};
+use chrono::{DateTime, NaiveDate, Utc};
 use directories::UserDirs;
 use std::path::PathBuf;
 use std::sync::Arc;
@@ -14,66 +20,286 @@ pub struct AppState {
 }
 
 #[tauri::command]
-pub fn start_timer(state: State<AppState>) -> Result<Session, String> {
-    state
-        .timer_manager
-        .start_session()
-        .map_err(|e| e.to_string())
+pub fn start_timer(
+    state: State<AppState>,
+    project_id: Option<String>,
+    budget: Option<SessionBudget>,
+) -> Result<Session, TimelyError> {
+    state.timer_manager.start_session(project_id, budget)
+}
+
+#[tauri::command]
+pub fn pause_timer(state: State<AppState>, reason: Option<String>) -> Result<Session, TimelyError> {
+    state.timer_manager.pause_session(reason)
+}
+
+#[tauri::command]
+pub fn resume_timer(state: State<AppState>) -> Result<Session, TimelyError> {
+    state.timer_manager.resume_session()
+}
+
+#[tauri::command]
+pub fn end_timer(state: State<AppState>) -> Result<Session, TimelyError> {
+    state.timer_manager.end_session()
 }
 
 #[tauri::command]
-pub fn pause_timer(state: State<AppState>) -> Result<Session, String> {
+pub fn get_timer_state(state: State<AppState>) -> TimerState {
+    state.timer_manager.get_state()
+}
+
+#[tauri::command]
+pub fn start_named_timer(
+    state: State<AppState>,
+    name: String,
+    project_id: Option<String>,
+    budget: Option<SessionBudget>,
+) -> Result<Session, TimelyError> {
     state
         .timer_manager
-        .pause_session()
-        .map_err(|e| e.to_string())
+        .start_named_session(&name, project_id, budget)
+}
+
+#[tauri::command]
+pub fn pause_named_timer(
+    state: State<AppState>,
+    name: String,
+    reason: Option<String>,
+) -> Result<Session, TimelyError> {
+    state.timer_manager.pause_named_session(&name, reason)
+}
+
+#[tauri::command]
+pub fn resume_named_timer(state: State<AppState>, name: String) -> Result<Session, TimelyError> {
+    state.timer_manager.resume_named_session(&name)
 }
 
 #[tauri::command]
-pub fn resume_timer(state: State<AppState>) -> Result<Session, String> {
+pub fn end_named_timer(state: State<AppState>, name: String) -> Result<Session, TimelyError> {
+    state.timer_manager.end_named_session(&name)
+}
+
+#[tauri::command]
+pub fn list_timers(state: State<AppState>) -> Vec<RunningTimer> {
+    state.timer_manager.list_timers()
+}
+
+#[tauri::command]
+pub fn set_primary_timer(state: State<AppState>, name: String) -> Result<(), TimelyError> {
+    state.timer_manager.set_primary_timer(&name)
+}
+
+#[tauri::command]
+pub fn start_pomodoro(
+    state: State<AppState>,
+    project_id: Option<String>,
+) -> Result<PomodoroState, TimelyError> {
+    state.timer_manager.start_pomodoro(project_id)
+}
+
+#[tauri::command]
+pub fn stop_pomodoro(state: State<AppState>) -> Result<(), TimelyError> {
+    state.timer_manager.stop_pomodoro()
+}
+
+#[tauri::command]
+pub fn get_pomodoro_settings(state: State<AppState>) -> Result<PomodoroSettings, TimelyError> {
+    state.session_store.get_pomodoro_settings()
+}
+
+#[tauri::command]
+pub fn save_pomodoro_settings(
+    state: State<AppState>,
+    settings: PomodoroSettings,
+) -> Result<(), TimelyError> {
+    state.session_store.save_pomodoro_settings(&settings)
+}
+
+#[tauri::command]
+pub fn get_hotkey_settings(state: State<AppState>) -> Result<HotkeySettings, TimelyError> {
+    state.session_store.get_hotkey_settings()
+}
+
+/// Save and re-register the global shortcuts, returning any that another
+/// application already holds
+#[tauri::command]
+pub fn save_hotkey_settings(
+    app: tauri::AppHandle,
+    state: State<AppState>,
+    settings: HotkeySettings,
+) -> Result<Vec<HotkeyConflict>, TimelyError> {
+    crate::hotkeys::parse(&settings)?;
+    state.session_store.save_hotkey_settings(&settings)?;
+    crate::hotkeys::register(&app, &settings)
+}
+
+#[tauri::command]
+pub fn get_current_elapsed(state: State<AppState>) -> i64 {
+    state.timer_manager.get_current_elapsed_seconds()
+}
+
+#[tauri::command]
+pub fn get_all_sessions(
+    state: State<AppState>,
+    tags: Option<Vec<String>>,
+) -> Result<Rows<Session>, TimelyError> {
     state
-        .timer_manager
-        .resume_session()
-        .map_err(|e| e.to_string())
+        .session_store
+        .get_all_sessions(&tags.unwrap_or_default())
+}
+
+#[tauri::command]
+pub fn query_sessions(
+    state: State<AppState>,
+    query: SessionQuery,
+) -> Result<SessionPage, TimelyError> {
+    state.session_store.query_sessions(&query)
+}
+
+#[tauri::command]
+pub fn create_manual_session(
+    state: State<AppState>,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    breaks: Option<Vec<Break>>,
+    project_id: Option<String>,
+    description: Option<String>,
+    tags: Option<Vec<String>>,
+) -> Result<Session, TimelyError> {
+    let mut session = Session::new(project_id);
+    session.start = start;
+    session.end = Some(end);
+    session.breaks = breaks.unwrap_or_default();
+    session.description = description;
+    session.tags = tags.unwrap_or_default();
+
+    state.session_store.create_manual_session(session)
 }
 
 #[tauri::command]
-pub fn end_timer(state: State<AppState>) -> Result<Session, String> {
+pub fn update_session(state: State<AppState>, session: Session) -> Result<Session, TimelyError> {
+    state.session_store.update_session(session)
+}
+
+#[tauri::command]
+pub fn delete_session(state: State<AppState>, id: String) -> Result<(), TimelyError> {
+    state.session_store.delete_session(&id)
+}
+
+/// Look for stored rows that can't be read or don't add up, repairing what
+/// can be repaired when `fix` is set
+#[tauri::command]
+pub fn check_integrity(
+    state: State<AppState>,
+    fix: Option<bool>,
+) -> Result<IntegrityReport, TimelyError> {
+    state.session_store.check_integrity(fix.unwrap_or(false))
+}
+
+#[tauri::command]
+pub fn set_session_description(
+    state: State<AppState>,
+    session_id: String,
+    description: Option<String>,
+) -> Result<Session, TimelyError> {
     state
         .timer_manager
-        .end_session()
-        .map_err(|e| e.to_string())
+        .set_session_description(&session_id, description)
 }
 
 #[tauri::command]
-pub fn get_timer_state(state: State<AppState>) -> TimerState {
-    if let Some(session) = state.timer_manager.get_current_session() {
-        let elapsed = state.timer_manager.get_current_elapsed_seconds();
-        println!("Timer state - Session ID: {}, Paused: {}, Elapsed: {}", 
-                 session.id, session.is_paused(), elapsed);
-        TimerState {
-            is_running: true,
-            is_paused: session.is_paused(),
-            current_session_id: Some(session.id.clone()),
-            elapsed_seconds: elapsed,
-        }
-    } else {
-        println!("No active session");
-        TimerState::default()
-    }
+pub fn set_session_tags(
+    state: State<AppState>,
+    session_id: String,
+    tags: Vec<String>,
+) -> Result<Session, TimelyError> {
+    state.timer_manager.set_session_tags(&session_id, tags)
 }
 
 #[tauri::command]
-pub fn get_current_elapsed() -> Result<i64, String> {
-    Ok(Utc::now().timestamp())
+pub fn get_all_tags(state: State<AppState>) -> Result<Vec<String>, TimelyError> {
+    state.session_store.get_all_tags()
 }
 
 #[tauri::command]
-pub fn get_all_sessions(state: State<AppState>) -> Result<Vec<Session>, String> {
-    state
+pub fn get_all_clients(state: State<AppState>) -> Result<Rows<Client>, TimelyError> {
+    state.session_store.get_all_clients()
+}
+
+#[tauri::command]
+pub fn create_client(state: State<AppState>, name: String) -> Result<Client, TimelyError> {
+    let client = Client::new(name);
+    state.session_store.save_client(&client)?;
+    Ok(client)
+}
+
+#[tauri::command]
+pub fn update_client(
+    state: State<AppState>,
+    id: String,
+    name: String,
+) -> Result<Client, TimelyError> {
+    let mut client = state
+        .session_store
+        .get_client(&id)?
+        .or_error(TimelyError::NotFound, "Client not found")?;
+
+    client.name = name;
+    state.session_store.save_client(&client)?;
+    Ok(client)
+}
+
+#[tauri::command]
+pub fn delete_client(state: State<AppState>, id: String) -> Result<(), TimelyError> {
+    state.session_store.delete_client(&id)
+}
+
+#[tauri::command]
+pub fn get_all_projects(state: State<AppState>) -> Result<Rows<Project>, TimelyError> {
+    state.session_store.get_all_projects()
+}
+
+#[tauri::command]
+pub fn create_project(
+    state: State<AppState>,
+    name: String,
+    client_id: Option<String>,
+) -> Result<Project, TimelyError> {
+    let project = Project::new(name, client_id);
+    state.session_store.save_project(&project)?;
+    Ok(project)
+}
+
+#[tauri::command]
+pub fn update_project(
+    state: State<AppState>,
+    id: String,
+    name: String,
+    client_id: Option<String>,
+) -> Result<Project, TimelyError> {
+    let mut project = state
         .session_store
-        .get_all_sessions()
-        .map_err(|e| e.to_string())
+        .get_project(&id)?
+        .or_error(TimelyError::NotFound, "Project not found")?;
+
+    project.name = name;
+    project.client_id = client_id;
+    state.session_store.save_project(&project)?;
+    Ok(project)
+}
+
+#[tauri::command]
+pub fn delete_project(state: State<AppState>, id: String) -> Result<(), TimelyError> {
+    state.session_store.delete_project(&id)
+}
+
+#[tauri::command]
+pub fn get_breaks(
+    state: State<AppState>,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> Result<Rows<Break>, TimelyError> {
+    state.session_store.get_breaks(from, to)
 }
 
 #[tauri::command]
@@ -81,72 +307,226 @@ pub fn get_monthly_summary(
     state: State<AppState>,
     year: i32,
     month: u32,
-) -> Result<MonthlySummary, String> {
+) -> Result<MonthlySummary, TimelyError> {
+    state.session_store.get_monthly_summary(year, month)
+}
+
+#[tauri::command]
+pub fn get_summary(
+    state: State<AppState>,
+    range: SummaryRange,
+    granularity: Granularity,
+    overlap: Option<OverlapMode>,
+) -> Result<Summary, TimelyError> {
     state
         .session_store
-        .get_monthly_summary(year, month)
-        .map_err(|e| e.to_string())
+        .get_summary(&range, granularity, overlap.unwrap_or_default())
+}
+
+#[tauri::command]
+pub fn get_daily_summary(
+    state: State<AppState>,
+    date: NaiveDate,
+    overlap: Option<OverlapMode>,
+) -> Result<Summary, TimelyError> {
+    state.session_store.get_summary(
+        &SummaryRange::Day { date },
+        Granularity::Day,
+        overlap.unwrap_or_default(),
+    )
+}
+
+#[tauri::command]
+pub fn get_weekly_summary(
+    state: State<AppState>,
+    year: i32,
+    week: u32,
+    overlap: Option<OverlapMode>,
+) -> Result<Summary, TimelyError> {
+    state.session_store.get_summary(
+        &SummaryRange::Week { year, week },
+        Granularity::Day,
+        overlap.unwrap_or_default(),
+    )
+}
+
+#[tauri::command]
+pub fn get_quarterly_summary(
+    state: State<AppState>,
+    year: i32,
+    quarter: u32,
+    overlap: Option<OverlapMode>,
+) -> Result<Summary, TimelyError> {
+    state.session_store.get_summary(
+        &SummaryRange::Quarter { year, quarter },
+        Granularity::Month,
+        overlap.unwrap_or_default(),
+    )
+}
+
+#[tauri::command]
+pub fn get_yearly_summary(
+    state: State<AppState>,
+    year: i32,
+    overlap: Option<OverlapMode>,
+) -> Result<Summary, TimelyError> {
+    state.session_store.get_summary(
+        &SummaryRange::Year { year },
+        Granularity::Month,
+        overlap.unwrap_or_default(),
+    )
 }
 
 #[tauri::command]
-pub async fn export_session(session: Session) -> Result<String, String> {
+pub fn get_range_summary(
+    state: State<AppState>,
+    start: NaiveDate,
+    end: NaiveDate,
+    granularity: Option<Granularity>,
+    overlap: Option<OverlapMode>,
+) -> Result<Summary, TimelyError> {
+    state.session_store.get_summary(
+        &SummaryRange::Custom { start, end },
+        granularity.unwrap_or_default(),
+        overlap.unwrap_or_default(),
+    )
+}
+
+#[tauri::command]
+pub fn get_work_rules(state: State<AppState>) -> Result<WorkRules, TimelyError> {
+    state.session_store.get_work_rules()
+}
+
+#[tauri::command]
+pub fn save_work_rules(state: State<AppState>, rules: WorkRules) -> Result<WorkRules, TimelyError> {
+    state.session_store.save_work_rules(&rules)?;
+    Ok(rules)
+}
+
+#[tauri::command]
+pub fn get_timezone(state: State<AppState>) -> Result<String, TimelyError> {
+    state
+        .session_store
+        .get_timezone()
+        .map(|tz| tz.name().to_string())
+}
+
+#[tauri::command]
+pub fn set_timezone(state: State<AppState>, timezone: String) -> Result<String, TimelyError> {
+    state
+        .session_store
+        .save_timezone(&timezone)
+        .map(|tz| tz.name().to_string())
+}
+
+#[tauri::command]
+pub fn get_idle_settings(state: State<AppState>) -> Result<IdleSettings, TimelyError> {
+    state.session_store.get_idle_settings()
+}
+
+#[tauri::command]
+pub fn save_idle_settings(
+    state: State<AppState>,
+    settings: IdleSettings,
+) -> Result<(), TimelyError> {
+    state.session_store.save_idle_settings(&settings)
+}
+
+#[tauri::command]
+pub fn get_pending_idle(state: State<AppState>) -> Option<IdleInterval> {
+    state.timer_manager.get_pending_idle()
+}
+
+#[tauri::command]
+pub fn resolve_idle(
+    state: State<AppState>,
+    resolution: IdleResolution,
+) -> Result<Session, TimelyError> {
+    state.timer_manager.resolve_idle(resolution)
+}
+
+#[tauri::command]
+pub fn get_pending_gaps(state: State<AppState>) -> Vec<SessionGap> {
+    state.timer_manager.get_pending_gaps()
+}
+
+#[tauri::command]
+pub fn resolve_gap(
+    state: State<AppState>,
+    session_id: String,
+    resolution: GapResolution,
+) -> Result<Session, TimelyError> {
+    state.timer_manager.resolve_gap(&session_id, resolution)
+}
+
+#[tauri::command]
+pub async fn export_session(
+    state: State<'_, AppState>,
+    session: Session,
+) -> Result<String, TimelyError> {
+    let tz = state.session_store.get_timezone()?;
+
     let downloads_dir = UserDirs::new()
         .and_then(|dirs| dirs.home_dir().to_path_buf().into())
         .map(|home: PathBuf| home.join("Downloads"))
-        .ok_or_else(|| "Could not determine downloads directory".to_string())?;
+        .or_error(
+            TimelyError::Export,
+            "Could not determine downloads directory",
+        )?;
 
     let filename = format!(
         "Session-{}.xlsx",
-        session.start.format("%Y-%m-%d-%H-%M")
+        session.start.with_timezone(&tz).format("%Y-%m-%d-%H-%M")
     );
     let output_path = downloads_dir.join(filename);
 
-    ExcelExporter::export_session(&session, output_path.clone())
-        .map_err(|e| e.to_string())?;
+    ExcelExporter::export_session(&session, tz, output_path.clone())?;
 
     Ok(output_path.to_string_lossy().to_string())
 }
 
-#[tauri::command]
-pub fn update_tray(
-    app: tauri::AppHandle,
-    is_running: bool,
-    is_paused: bool,
-    elapsed_seconds: i64,
-) -> Result<(), String> {
-    crate::system_tray::update_tray_menu(&app, is_running, is_paused, elapsed_seconds)
-        .map_err(|e| e.to_string())
-}
-
 #[tauri::command]
 pub async fn export_monthly_summary(
     state: State<'_, AppState>,
     year: i32,
     month: u32,
-) -> Result<String, String> {
-    let _summary = state.session_store.get_monthly_summary(year, month)
-        .map_err(|e| e.to_string())?;
+) -> Result<MonthlyExport, TimelyError> {
+    let summary = state.session_store.get_monthly_summary(year, month)?;
     
-    let sessions = state.session_store.get_all_sessions()
-        .map_err(|e| e.to_string())?
-        .into_iter()
-        .filter(|s| {
-            let start_year = s.start.year();
-            let start_month = s.start.month();
-            start_year == year && start_month == month
-        })
-        .collect::<Vec<_>>();
+    let sessions = state.session_store.get_sessions_for_month(year, month)?;
+    // The summary reports the same session rows the list leaves out
+    let mut skipped = summary.skipped.clone();
+    for row in sessions.skipped {
+        if !skipped
+            .iter()
+            .any(|s| s.table == row.table && s.id == row.id)
+        {
+            skipped.push(row);
+        }
+    }
+    let tz = state.session_store.get_timezone()?;
 
     let downloads_dir = UserDirs::new()
         .and_then(|dirs| dirs.home_dir().to_path_buf().into())
         .map(|home: PathBuf| home.join("Downloads"))
-        .ok_or_else(|| "Could not determine downloads directory".to_string())?;
+        .or_error(
+            TimelyError::Export,
+            "Could not determine downloads directory",
+        )?;
 
     let filename = format!("MonthlySummary-{}-{:02}.xlsx", year, month);
     let output_path = downloads_dir.join(filename);
 
-    ExcelExporter::export_monthly_summary(year, month, &sessions, output_path.clone())
-        .map_err(|e| e.to_string())?;
+    ExcelExporter::export_monthly_summary(
+        &summary,
+        &sessions.rows,
+        &skipped,
+        tz,
+        output_path.clone(),
+    )?;
 
-    Ok(output_path.to_string_lossy().to_string())
+    Ok(MonthlyExport {
+        path: output_path.to_string_lossy().to_string(),
+        skipped,
+    })
 }
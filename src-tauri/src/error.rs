@@ -0,0 +1,130 @@
+use serde::ser::SerializeStruct;
+use serde::{Serialize, Serializer};
+use std::fmt::Display;
+
+pub type Result<T, E = TimelyError> = std::result::Result<T, E>;
+
+/// Everything that can go wrong in the timer, store and exports. Commands
+/// return it to the frontend as `{ "code": ..., "message": ... }`, where the
+/// code is stable and the message is for people.
+#[derive(Debug, thiserror::Error)]
+pub enum TimelyError {
+    /// A session, project, client, timer or pending decision that isn't there
+    #[error("{0}")]
+    NotFound(String),
+    /// Something is already running that only one of may run
+    #[error("{0}")]
+    AlreadyActive(String),
+    #[error("{0}")]
+    AlreadyPaused(String),
+    #[error("{0}")]
+    NotPaused(String),
+    /// Input that doesn't make sense: bad times, budgets, settings, dates
+    #[error("{0}")]
+    Validation(String),
+    /// The action clashes with the current state, e.g. editing a running session
+    #[error("{0}")]
+    Conflict(String),
+    /// The database couldn't be read or written
+    #[error("{0}")]
+    Storage(String),
+    /// A spreadsheet couldn't be written
+    #[error("{0}")]
+    Export(String),
+    /// A failure in the platform around us, e.g. registering shortcuts
+    #[error("{0}")]
+    Internal(String),
+}
+
+impl TimelyError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            TimelyError::NotFound(_) => "not_found",
+            TimelyError::AlreadyActive(_) => "already_active",
+            TimelyError::AlreadyPaused(_) => "already_paused",
+            TimelyError::NotPaused(_) => "not_paused",
+            TimelyError::Validation(_) => "validation",
+            TimelyError::Conflict(_) => "conflict",
+            TimelyError::Storage(_) => "storage",
+            TimelyError::Export(_) => "export",
+            TimelyError::Internal(_) => "internal",
+        }
+    }
+}
+
+impl Serialize for TimelyError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut error = serializer.serialize_struct("TimelyError", 2)?;
+        error.serialize_field("code", self.code())?;
+        error.serialize_field("message", &self.to_string())?;
+        error.end()
+    }
+}
+
+impl From<rusqlite::Error> for TimelyError {
+    fn from(e: rusqlite::Error) -> Self {
+        TimelyError::Storage(e.to_string())
+    }
+}
+
+/// Values stored as JSON, e.g. settings
+impl From<serde_json::Error> for TimelyError {
+    fn from(e: serde_json::Error) -> Self {
+        TimelyError::Storage(e.to_string())
+    }
+}
+
+impl From<rust_xlsxwriter::XlsxError> for TimelyError {
+    fn from(e: rust_xlsxwriter::XlsxError) -> Self {
+        TimelyError::Export(e.to_string())
+    }
+}
+
+/// Return early with a `TimelyError` of the given kind, like `anyhow::bail!`:
+/// `fail!(Validation, "Budget must be positive")`
+macro_rules! fail {
+    ($kind:ident, $($arg:tt)+) => {
+        return Err($crate::error::TimelyError::$kind(format!($($arg)+)))
+    };
+}
+pub(crate) use fail;
+
+/// Like `anyhow::Context`, but saying which kind of error a missing value or
+/// lower-level failure becomes, e.g.
+/// `.or_error(TimelyError::NotFound, "Session not found")`
+pub trait OrError<T> {
+    fn or_error(self, kind: fn(String) -> TimelyError, message: &str) -> Result<T>;
+}
+
+impl<T> OrError<T> for Option<T> {
+    fn or_error(self, kind: fn(String) -> TimelyError, message: &str) -> Result<T> {
+        self.ok_or_else(|| kind(message.to_string()))
+    }
+}
+
+impl<T, E: Display> OrError<T> for std::result::Result<T, E> {
+    fn or_error(self, kind: fn(String) -> TimelyError, message: &str) -> Result<T> {
+        self.map_err(|e| kind(format!("{}: {}", message, e)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn serializes_with_a_stable_code() {
+        let error = TimelyError::NotFound("Session not found".to_string());
+        assert_eq!(
+            serde_json::to_value(&error).unwrap(),
+            serde_json::json!({ "code": "not_found", "message": "Session not found" })
+        );
+
+        let failed: std::result::Result<(), _> = Err("disk full");
+        let error = failed
+            .or_error(TimelyError::Storage, "Failed to save session")
+            .unwrap_err();
+        assert_eq!(error.code(), "storage");
+        assert_eq!(error.to_string(), "Failed to save session: disk full");
+    }
+}
@@ -1,12 +1,14 @@
-use crate::models::Session;
-use anyhow::Result;
+use crate::models::{MonthlySummary, Session, SkippedRow};
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+use crate::error::Result;
 use rust_xlsxwriter::*;
 use std::path::PathBuf;
 
 pub struct ExcelExporter;
 
 impl ExcelExporter {
-    pub fn export_session(session: &Session, output_path: PathBuf) -> Result<PathBuf> {
+    pub fn export_session(session: &Session, tz: Tz, output_path: PathBuf) -> Result<PathBuf> {
         let mut workbook = Workbook::new();
         let worksheet = workbook.add_worksheet();
 
@@ -29,33 +31,47 @@ impl ExcelExporter {
         worksheet.write(2, 1, &session.id)?;
 
         worksheet.write_with_format(3, 0, "Start Time:", &header_format)?;
-        worksheet.write(3, 1, session.start.format("%Y-%m-%d %H:%M:%S").to_string())?;
+        worksheet.write(3, 1, format_local(session.start, tz, "%Y-%m-%d %H:%M:%S"))?;
 
         if let Some(end) = session.end {
             worksheet.write_with_format(4, 0, "End Time:", &header_format)?;
-            worksheet.write(4, 1, end.format("%Y-%m-%d %H:%M:%S").to_string())?;
+            worksheet.write(4, 1, format_local(end, tz, "%Y-%m-%d %H:%M:%S"))?;
         }
 
         worksheet.write_with_format(5, 0, "Total Time:", &header_format)?;
         worksheet.write(5, 1, format_duration(session.total_seconds))?;
 
-        // Pause/Resume history
-        if !session.pauses.is_empty() {
-            worksheet.write_with_format(7, 0, "Pause/Resume History", &title_format)?;
+        if let Some(budget) = &session.budget {
+            let difference = session.total_seconds - budget.seconds;
+            let status = if difference > 0 {
+                format!("{} over", format_duration(difference))
+            } else {
+                format!("{} under", format_duration(-difference))
+            };
+            worksheet.write_with_format(6, 0, "Budget:", &header_format)?;
+            worksheet.write(6, 1, format!("{} ({})", format_duration(budget.seconds), status))?;
+        }
+
+        // Break history
+        if !session.breaks.is_empty() {
+            worksheet.set_column_width(2, 12)?;
+            worksheet.set_column_width(3, 20)?;
+
+            worksheet.write_with_format(7, 0, "Breaks", &title_format)?;
             
-            worksheet.write_with_format(8, 0, "Event", &header_format)?;
-            worksheet.write_with_format(8, 1, "Time", &header_format)?;
-
-            let mut row = 9;
-            for (i, pause) in session.pauses.iter().enumerate() {
-                worksheet.write(row, 0, "Paused")?;
-                worksheet.write(row, 1, pause.format("%Y-%m-%d %H:%M:%S").to_string())?;
-                row += 1;
-
-                if let Some(resume) = session.resumes.get(i) {
-                    worksheet.write(row, 0, "Resumed")?;
-                    worksheet.write(row, 1, resume.format("%Y-%m-%d %H:%M:%S").to_string())?;
-                    row += 1;
+            worksheet.write_with_format(8, 0, "Started", &header_format)?;
+            worksheet.write_with_format(8, 1, "Ended", &header_format)?;
+            worksheet.write_with_format(8, 2, "Duration", &header_format)?;
+            worksheet.write_with_format(8, 3, "Reason", &header_format)?;
+
+            for (row, b) in (9..).zip(session.breaks.iter()) {
+                worksheet.write(row, 0, format_local(b.started_at, tz, "%Y-%m-%d %H:%M:%S"))?;
+                if let Some(ended_at) = b.ended_at {
+                    worksheet.write(row, 1, format_local(ended_at, tz, "%Y-%m-%d %H:%M:%S"))?;
+                    worksheet.write(row, 2, format_duration((ended_at - b.started_at).num_seconds()))?;
+                }
+                if let Some(reason) = &b.reason {
+                    worksheet.write(row, 3, reason)?;
                 }
             }
         }
@@ -65,9 +81,10 @@ impl ExcelExporter {
     }
 
     pub fn export_monthly_summary(
-        year: i32,
-        month: u32,
+        summary: &MonthlySummary,
         sessions: &[Session],
+        skipped: &[SkippedRow],
+        tz: Tz,
         output_path: PathBuf,
     ) -> Result<PathBuf> {
         let mut workbook = Workbook::new();
@@ -88,40 +105,76 @@ impl ExcelExporter {
         worksheet.write_with_format(
             0,
             0,
-            format!("Monthly Summary - {}-{:02}", year, month),
+            format!("Monthly Summary - {}-{:02}", summary.year, summary.month),
             &title_format,
         )?;
 
         // Summary statistics
         let header_format = Format::new().set_bold();
-        let total_seconds: i64 = sessions.iter().map(|s| s.total_seconds).sum();
-        let longest_session = sessions
-            .iter()
-            .map(|s| s.total_seconds)
-            .max()
-            .unwrap_or(0);
+        let hours_format = Format::new().set_num_format("0.00");
 
         worksheet.write_with_format(2, 0, "Total Sessions:", &header_format)?;
-        worksheet.write(2, 1, sessions.len() as f64)?;
+        worksheet.write(2, 1, summary.session_count as f64)?;
 
         worksheet.write_with_format(3, 0, "Total Time:", &header_format)?;
-        worksheet.write(3, 1, format_duration(total_seconds))?;
+        worksheet.write(3, 1, format_duration(summary.total_seconds))?;
 
         worksheet.write_with_format(4, 0, "Longest Session:", &header_format)?;
-        worksheet.write(4, 1, format_duration(longest_session))?;
+        worksheet.write(4, 1, format_duration(summary.longest_session_seconds))?;
+
+        worksheet.write_with_format(5, 0, "Regular Hours:", &header_format)?;
+        worksheet.write_with_format(5, 1, summary.regular_hours, &hours_format)?;
+
+        worksheet.write_with_format(6, 0, "Overtime Hours:", &header_format)?;
+        worksheet.write_with_format(6, 1, summary.overtime_hours, &hours_format)?;
+
+        worksheet.write_with_format(7, 0, "Weighted Hours:", &header_format)?;
+        worksheet.write_with_format(7, 1, summary.weighted_hours, &hours_format)?;
+
+        // Rows that couldn't be read aren't in the figures or the session list
+        if !skipped.is_empty() {
+            worksheet.write_with_format(8, 0, "Unreadable Rows:", &header_format)?;
+            worksheet.write(
+                8,
+                1,
+                format!(
+                    "{} left out; run the integrity check to find them",
+                    skipped.len()
+                ),
+            )?;
+        }
+
+        // Weekly breakdown
+        worksheet.write_with_format(9, 0, "Weekly Breakdown", &title_format)?;
+
+        worksheet.write_with_format(10, 0, "Week Start", &header_format)?;
+        worksheet.write_with_format(10, 1, "Week End", &header_format)?;
+        worksheet.write_with_format(10, 2, "Regular Hours", &header_format)?;
+        worksheet.write_with_format(10, 3, "Overtime Hours", &header_format)?;
+
+        let mut row = 11;
+        for week in &summary.weekly_breakdown {
+            worksheet.write(row, 0, week.week_start.format("%Y-%m-%d").to_string())?;
+            worksheet.write(row, 1, week.week_end.format("%Y-%m-%d").to_string())?;
+            worksheet.write_with_format(row, 2, week.regular_hours, &hours_format)?;
+            worksheet.write_with_format(row, 3, week.overtime_hours, &hours_format)?;
+            row += 1;
+        }
 
         // Sessions list
-        worksheet.write_with_format(6, 0, "Session Details", &title_format)?;
+        row += 1;
+        worksheet.write_with_format(row, 0, "Session Details", &title_format)?;
+        row += 1;
 
-        worksheet.write_with_format(7, 0, "Date", &header_format)?;
-        worksheet.write_with_format(7, 1, "Start Time", &header_format)?;
-        worksheet.write_with_format(7, 2, "Duration", &header_format)?;
-        worksheet.write_with_format(7, 3, "Session ID", &header_format)?;
+        worksheet.write_with_format(row, 0, "Date", &header_format)?;
+        worksheet.write_with_format(row, 1, "Start Time", &header_format)?;
+        worksheet.write_with_format(row, 2, "Duration", &header_format)?;
+        worksheet.write_with_format(row, 3, "Session ID", &header_format)?;
+        row += 1;
 
-        let mut row = 8;
         for session in sessions {
-            worksheet.write(row, 0, session.start.format("%Y-%m-%d").to_string())?;
-            worksheet.write(row, 1, session.start.format("%H:%M:%S").to_string())?;
+            worksheet.write(row, 0, format_local(session.start, tz, "%Y-%m-%d"))?;
+            worksheet.write(row, 1, format_local(session.start, tz, "%H:%M:%S"))?;
             worksheet.write(row, 2, format_duration(session.total_seconds))?;
             worksheet.write(row, 3, &session.id)?;
             row += 1;
@@ -132,6 +185,10 @@ impl ExcelExporter {
     }
 }
 
+fn format_local(instant: DateTime<Utc>, tz: Tz, fmt: &str) -> String {
+    instant.with_timezone(&tz).format(fmt).to_string()
+}
+
 fn format_duration(seconds: i64) -> String {
     let hours = seconds / 3600;
     let minutes = (seconds % 3600) / 60;
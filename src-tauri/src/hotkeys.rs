@@ -0,0 +1,115 @@
+use crate::commands::AppState;
+use crate::error::{Result, TimelyError};
+use crate::models::{HotkeyAction, HotkeyConflict, HotkeySettings, Session};
+use crate::notify;
+use crate::system_tray::format_elapsed;
+use tauri::{AppHandle, Manager, Runtime};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut, ShortcutState};
+
+/// The bound shortcuts, failing on the first one that isn't a valid accelerator
+pub fn parse(settings: &HotkeySettings) -> Result<Vec<(HotkeyAction, String, Shortcut)>> {
+    settings
+        .bindings()
+        .into_iter()
+        .map(|(action, binding)| {
+            let shortcut = binding.parse::<Shortcut>().map_err(|_| {
+                TimelyError::Validation(format!(
+                    "{} shortcut \"{}\" isn't a valid shortcut",
+                    action.label(),
+                    binding
+                ))
+            })?;
+            Ok((action, binding.to_string(), shortcut))
+        })
+        .collect()
+}
+
+/// Replace the registered shortcuts with those in `settings`. Shortcuts the
+/// system refuses, typically because another application holds them, are
+/// skipped and returned so the user can pick others.
+pub fn register<R: Runtime>(
+    app: &AppHandle<R>,
+    settings: &HotkeySettings,
+) -> Result<Vec<HotkeyConflict>> {
+    let shortcuts = parse(settings)?;
+    let global = app.global_shortcut();
+    global
+        .unregister_all()
+        .map_err(|e| TimelyError::Internal(e.to_string()))?;
+    if !settings.enabled {
+        return Ok(Vec::new());
+    }
+
+    let mut conflicts = Vec::new();
+    for (action, binding, shortcut) in shortcuts {
+        let registered = global.on_shortcut(shortcut, move |app, _, event| {
+            if event.state() == ShortcutState::Pressed {
+                run(app, action);
+            }
+        });
+        if let Err(e) = registered {
+            conflicts.push(HotkeyConflict {
+                action,
+                shortcut: binding,
+                message: e.to_string(),
+            });
+        }
+    }
+
+    Ok(conflicts)
+}
+
+/// Tell the user about shortcuts that couldn't be registered
+pub fn announce_conflicts<R: Runtime>(app: &AppHandle<R>, conflicts: &[HotkeyConflict]) {
+    for conflict in conflicts {
+        eprintln!(
+            "Couldn't register {} for {}: {}",
+            conflict.shortcut,
+            conflict.action.label(),
+            conflict.message
+        );
+        notify(
+            app,
+            "Keyboard shortcut unavailable",
+            format!(
+                "{} is taken by another application, so it won't {} the timer.",
+                conflict.shortcut,
+                conflict.action.label().to_lowercase()
+            ),
+        );
+    }
+}
+
+/// Carry out a shortcut's action and confirm it, or say why it couldn't be done
+fn run<R: Runtime>(app: &AppHandle<R>, action: HotkeyAction) {
+    let state = app.state::<AppState>();
+    let timer = &state.timer_manager;
+    let result = match action {
+        HotkeyAction::Start => timer.start_session(None, None),
+        HotkeyAction::Pause => timer.pause_session(None),
+        HotkeyAction::Resume => timer.resume_session(),
+        HotkeyAction::End => timer.end_session(),
+    };
+
+    match result {
+        Ok(session) => {
+            let (title, body) = confirmation(action, &session);
+            notify(app, title, body);
+        }
+        Err(e) => notify(
+            app,
+            &format!("Couldn't {} the timer", action.label().to_lowercase()),
+            e.to_string(),
+        ),
+    }
+}
+
+fn confirmation(action: HotkeyAction, session: &Session) -> (&'static str, String) {
+    let elapsed = format_elapsed(session.calculate_total_seconds());
+    match action {
+        HotkeyAction::Start => ("Timer started", "Tracking time from now.".to_string()),
+        HotkeyAction::Pause => ("Timer paused", format!("{} tracked so far.", elapsed)),
+        HotkeyAction::Resume => ("Timer resumed", format!("{} tracked so far.", elapsed)),
+        HotkeyAction::End => ("Session ended", format!("{} tracked in total.", elapsed)),
+    }
+}
@@ -0,0 +1,306 @@
+use crate::models::IdleEvent;
+use crate::session_store::SessionStore;
+use crate::timer_manager::TimerManager;
+use anyhow::Result;
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+/// How often the watcher asks the monitor for the idle time
+const POLL_INTERVAL: Duration = Duration::from_secs(15);
+
+/// A source of how long the user has gone without keyboard or pointer input
+pub trait IdleMonitor: Send + Sync {
+    fn idle_time(&self) -> Result<Duration>;
+}
+
+/// The idle monitor for the platform we're running on
+pub fn system_monitor() -> Arc<dyn IdleMonitor> {
+    #[cfg(target_os = "linux")]
+    {
+        Arc::new(linux::ScreenSaverIdleMonitor::new())
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        Arc::new(NeverIdle)
+    }
+}
+
+/// Stand-in for platforms without idle detection; never reports idle time
+#[cfg(not(target_os = "linux"))]
+struct NeverIdle;
+
+#[cfg(not(target_os = "linux"))]
+impl IdleMonitor for NeverIdle {
+    fn idle_time(&self) -> Result<Duration> {
+        Ok(Duration::ZERO)
+    }
+}
+
+/// Check the idle time once and let the timer react to it
+pub fn poll(
+    monitor: &dyn IdleMonitor,
+    timer: &TimerManager,
+    store: &SessionStore,
+) -> Result<Option<IdleEvent>> {
+    let settings = store.get_idle_settings()?;
+    if !settings.enabled && timer.get_pending_idle().is_none() {
+        return Ok(None);
+    }
+
+    let idle_for = chrono::Duration::from_std(monitor.idle_time()?)?;
+    Ok(timer.check_idle(idle_for, &settings)?)
+}
+
+/// Poll the monitor on a background thread for the lifetime of the app,
+/// handing any idle events to `on_event`
+pub fn spawn_watcher(
+    monitor: Arc<dyn IdleMonitor>,
+    timer: Arc<TimerManager>,
+    store: Arc<SessionStore>,
+    on_event: impl Fn(IdleEvent) + Send + 'static,
+) {
+    thread::spawn(move || loop {
+        match poll(monitor.as_ref(), &timer, &store) {
+            Ok(Some(event)) => on_event(event),
+            Ok(None) => {}
+            Err(e) => eprintln!("Idle check failed: {}", e),
+        }
+        thread::sleep(POLL_INTERVAL);
+    });
+}
+
+#[cfg(target_os = "linux")]
+mod linux {
+    use super::IdleMonitor;
+    use anyhow::{bail, Context, Result};
+    use std::process::Command;
+    use std::time::Duration;
+    use zbus::blocking::Connection;
+
+    /// Asks the desktop how long the session has been idle. GNOME's Mutter idle
+    /// monitor and the freedesktop ScreenSaver service (KDE and others) answer
+    /// over D-Bus on both Wayland and X11; bare X11 window managers fall back to
+    /// the XScreenSaver extension through `xprintidle`.
+    pub struct ScreenSaverIdleMonitor {
+        bus: Option<Connection>,
+    }
+
+    impl ScreenSaverIdleMonitor {
+        pub fn new() -> Self {
+            Self {
+                bus: Connection::session().ok(),
+            }
+        }
+
+        fn mutter_idle_time(bus: &Connection) -> Result<Duration> {
+            let reply = bus.call_method(
+                Some("org.gnome.Mutter.IdleMonitor"),
+                "/org/gnome/Mutter/IdleMonitor/Core",
+                Some("org.gnome.Mutter.IdleMonitor"),
+                "GetIdletime",
+                &(),
+            )?;
+            let millis: u64 = reply.body().deserialize()?;
+            Ok(Duration::from_millis(millis))
+        }
+
+        fn screensaver_idle_time(bus: &Connection) -> Result<Duration> {
+            let reply = bus.call_method(
+                Some("org.freedesktop.ScreenSaver"),
+                "/org/freedesktop/ScreenSaver",
+                Some("org.freedesktop.ScreenSaver"),
+                "GetSessionIdleTime",
+                &(),
+            )?;
+            let millis: u32 = reply.body().deserialize()?;
+            Ok(Duration::from_millis(millis as u64))
+        }
+
+        fn xprintidle() -> Result<Duration> {
+            let output = Command::new("xprintidle")
+                .output()
+                .context("xprintidle is not available")?;
+            if !output.status.success() {
+                bail!("xprintidle failed");
+            }
+            let millis: u64 = String::from_utf8_lossy(&output.stdout)
+                .trim()
+                .parse()
+                .context("Unexpected xprintidle output")?;
+            Ok(Duration::from_millis(millis))
+        }
+    }
+
+    impl IdleMonitor for ScreenSaverIdleMonitor {
+        fn idle_time(&self) -> Result<Duration> {
+            if let Some(bus) = &self.bus {
+                if let Ok(idle) = Self::mutter_idle_time(bus) {
+                    return Ok(idle);
+                }
+                if let Ok(idle) = Self::screensaver_idle_time(bus) {
+                    return Ok(idle);
+                }
+            }
+            Self::xprintidle().context("No idle time source available on this desktop")
+        }
+    }
+}
+
+/// Idle monitor reporting whatever idle time the test sets
+#[cfg(test)]
+#[derive(Default)]
+pub struct FakeIdleMonitor {
+    idle: std::sync::Mutex<Duration>,
+}
+
+#[cfg(test)]
+impl FakeIdleMonitor {
+    pub fn set_idle(&self, idle: Duration) {
+        *self.idle.lock().unwrap() = idle;
+    }
+}
+
+#[cfg(test)]
+impl IdleMonitor for FakeIdleMonitor {
+    fn idle_time(&self) -> Result<Duration> {
+        Ok(*self.idle.lock().unwrap())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{IdleResolution, Session};
+    use crate::test_utils::TempDir;
+    use crate::timer_manager::IDLE_BREAK_REASON;
+
+    struct Fixture {
+        _dir: TempDir,
+        store: Arc<SessionStore>,
+        timer: TimerManager,
+        monitor: FakeIdleMonitor,
+    }
+
+    /// A timer with a session that started an hour ago
+    fn running() -> Fixture {
+        let dir = TempDir::new();
+        let store = Arc::new(SessionStore::new(dir.path().join("idle.db")).unwrap());
+        let mut session = Session::new(None);
+        session.start -= chrono::Duration::hours(1);
+        session.last_seen_at = Some(chrono::Utc::now());
+        store.save_session(&session).unwrap();
+
+        let timer = TimerManager::new(store.clone()).unwrap();
+        Fixture {
+            _dir: dir,
+            store,
+            timer,
+            monitor: FakeIdleMonitor::default(),
+        }
+    }
+
+    fn minutes(m: u64) -> Duration {
+        Duration::from_secs(m * 60)
+    }
+
+    fn poll(f: &Fixture) -> Option<IdleEvent> {
+        super::poll(&f.monitor, &f.timer, &f.store).unwrap()
+    }
+
+    #[test]
+    fn pauses_after_threshold_backdated_to_last_input() {
+        let f = running();
+
+        f.monitor.set_idle(minutes(2));
+        assert_eq!(poll(&f), None);
+
+        f.monitor.set_idle(minutes(10));
+        let Some(IdleEvent::AutoPaused(interval)) = poll(&f) else {
+            panic!("expected the session to auto-pause");
+        };
+
+        let session = f.timer.get_current_session().unwrap();
+        assert!(session.is_paused());
+        let idle_break = session.breaks.last().unwrap();
+        assert_eq!(idle_break.started_at, interval.started_at);
+        assert_eq!(idle_break.reason.as_deref(), Some(IDLE_BREAK_REASON));
+        assert!(chrono::Utc::now() - interval.started_at >= chrono::Duration::minutes(10));
+
+        // Still away: nothing new to report
+        f.monitor.set_idle(minutes(20));
+        assert_eq!(poll(&f), None);
+    }
+
+    #[test]
+    fn reports_return_and_discards_idle_time() {
+        let f = running();
+        f.monitor.set_idle(minutes(30));
+        poll(&f);
+
+        f.monitor.set_idle(Duration::ZERO);
+        let Some(IdleEvent::Returned(interval)) = poll(&f) else {
+            panic!("expected a return event");
+        };
+        assert!(interval.returned_at.is_some());
+
+        let session = f.timer.resolve_idle(IdleResolution::Discard).unwrap();
+        assert!(!session.is_paused());
+        assert_eq!(session.breaks.last().unwrap().ended_at, interval.returned_at);
+        assert!(session.calculate_total_seconds() <= 30 * 60 + 1);
+        assert!(f.timer.get_pending_idle().is_none());
+    }
+
+    #[test]
+    fn keeping_idle_time_removes_the_break() {
+        let f = running();
+        f.monitor.set_idle(minutes(30));
+        poll(&f);
+        f.monitor.set_idle(Duration::ZERO);
+        poll(&f);
+
+        let session = f.timer.resolve_idle(IdleResolution::Keep).unwrap();
+        assert!(session.breaks.is_empty());
+        assert!(session.calculate_total_seconds() >= 60 * 60);
+
+        let stored = f.store.get_session(&session.id).unwrap().unwrap();
+        assert!(stored.breaks.is_empty());
+    }
+
+    #[test]
+    fn reassigning_records_the_reason() {
+        let f = running();
+        f.monitor.set_idle(minutes(30));
+        poll(&f);
+        f.monitor.set_idle(Duration::ZERO);
+        poll(&f);
+
+        assert!(f
+            .timer
+            .resolve_idle(IdleResolution::Reassign { reason: " ".into() })
+            .is_err());
+        let session = f
+            .timer
+            .resolve_idle(IdleResolution::Reassign { reason: "Meeting".into() })
+            .unwrap();
+        assert_eq!(session.breaks.last().unwrap().reason.as_deref(), Some("Meeting"));
+        assert!(session.breaks.last().unwrap().ended_at.is_some());
+    }
+
+    #[test]
+    fn disabled_or_paused_sessions_are_left_alone() {
+        let f = running();
+        let mut settings = f.store.get_idle_settings().unwrap();
+        settings.enabled = false;
+        f.store.save_idle_settings(&settings).unwrap();
+
+        f.monitor.set_idle(minutes(60));
+        assert_eq!(poll(&f), None);
+
+        settings.enabled = true;
+        f.store.save_idle_settings(&settings).unwrap();
+        f.timer.pause_session(None).unwrap();
+        assert_eq!(poll(&f), None);
+        assert!(f.timer.get_pending_idle().is_none());
+    }
+}
@@ -0,0 +1,205 @@
+use crate::local_time;
+use crate::models::Session;
+use chrono::{DateTime, NaiveDate, Utc};
+use chrono_tz::Tz;
+use std::collections::BTreeMap;
+
+/// The stretches of time a session was actually worked: from its start to
+/// `end` with breaks cut out. Breaks are expected in start order, as stored.
+pub fn worked_intervals(session: &Session, end: DateTime<Utc>) -> Vec<(DateTime<Utc>, DateTime<Utc>)> {
+    let mut intervals = Vec::new();
+    let mut cursor = session.start;
+
+    for b in &session.breaks {
+        if cursor >= end {
+            break;
+        }
+        if b.started_at > cursor {
+            intervals.push((cursor, b.started_at.min(end)));
+        }
+        cursor = cursor.max(b.ended_at.unwrap_or(end));
+    }
+
+    if cursor < end {
+        intervals.push((cursor, end));
+    }
+
+    intervals
+}
+
+/// Apportion a session's worked time across the local calendar days it
+/// touches, so a session from 22:00 to 03:00 counts two hours on the first
+/// day and three on the second. Returns seconds per day.
+pub fn seconds_per_day(session: &Session, end: DateTime<Utc>, tz: Tz) -> BTreeMap<NaiveDate, i64> {
+    split_by_day(&worked_intervals(session, end), tz)
+}
+
+/// Seconds per local calendar day covered by `intervals`
+pub fn split_by_day(intervals: &[(DateTime<Utc>, DateTime<Utc>)], tz: Tz) -> BTreeMap<NaiveDate, i64> {
+    let mut millis: BTreeMap<NaiveDate, i64> = BTreeMap::new();
+
+    for &(mut from, to) in intervals {
+        while from < to {
+            let day = local_time::local_date(tz, from);
+            let next_day = day.succ_opt().expect("date out of range");
+            let until = local_time::start_of_day(tz, next_day).min(to);
+
+            *millis.entry(day).or_insert(0) += (until - from).num_milliseconds();
+            from = until;
+        }
+    }
+
+    // Sum in milliseconds and round once per day so pieces don't each lose a fraction
+    millis
+        .into_iter()
+        .map(|(day, ms)| (day, (ms + 500) / 1000))
+        .collect()
+}
+
+/// The parts of `intervals` not already in `covered`, adding them to
+/// `covered`. Feeding sessions through in turn counts time they overlap only
+/// once, for the first session. `covered` is kept sorted and non-overlapping.
+pub fn take_uncovered(
+    intervals: &[(DateTime<Utc>, DateTime<Utc>)],
+    covered: &mut Vec<(DateTime<Utc>, DateTime<Utc>)>,
+) -> Vec<(DateTime<Utc>, DateTime<Utc>)> {
+    let mut uncovered = Vec::new();
+
+    for &(from, to) in intervals {
+        let mut cursor = from;
+        for &(covered_from, covered_to) in covered.iter() {
+            if covered_to <= cursor {
+                continue;
+            }
+            if covered_from >= to {
+                break;
+            }
+            if covered_from > cursor {
+                uncovered.push((cursor, covered_from));
+            }
+            cursor = cursor.max(covered_to);
+        }
+        if cursor < to {
+            uncovered.push((cursor, to));
+        }
+    }
+
+    // Merge the new pieces into the covered set
+    covered.extend(uncovered.iter().copied());
+    covered.sort();
+    let mut merged: Vec<(DateTime<Utc>, DateTime<Utc>)> = Vec::with_capacity(covered.len());
+    for &(from, to) in covered.iter() {
+        match merged.last_mut() {
+            Some(last) if from <= last.1 => last.1 = last.1.max(to),
+            _ => merged.push((from, to)),
+        }
+    }
+    *covered = merged;
+
+    uncovered
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Break;
+    use chrono_tz::Europe::Berlin;
+
+    fn utc(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+    }
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    fn session(start: &str, end: &str, breaks: &[(&str, &str)]) -> Session {
+        let mut session = Session::new(None);
+        session.start = utc(start);
+        session.end = Some(utc(end));
+        for (from, to) in breaks {
+            let mut b = Break::new(&session.id, utc(from));
+            b.ended_at = Some(utc(to));
+            session.breaks.push(b);
+        }
+        session
+    }
+
+    #[test]
+    fn cuts_breaks_out_of_the_session() {
+        let s = session(
+            "2025-11-03T09:00:00Z",
+            "2025-11-03T17:00:00Z",
+            &[("2025-11-03T12:00:00Z", "2025-11-03T13:00:00Z")],
+        );
+
+        assert_eq!(
+            worked_intervals(&s, s.end.unwrap()),
+            vec![
+                (utc("2025-11-03T09:00:00Z"), utc("2025-11-03T12:00:00Z")),
+                (utc("2025-11-03T13:00:00Z"), utc("2025-11-03T17:00:00Z")),
+            ]
+        );
+    }
+
+    #[test]
+    fn splits_across_midnight_and_month_end() {
+        let s = session("2025-10-31T22:00:00Z", "2025-11-01T03:00:00Z", &[]);
+        let days = seconds_per_day(&s, s.end.unwrap(), Tz::UTC);
+
+        assert_eq!(days.get(&date(2025, 10, 31)), Some(&(2 * 3600)));
+        assert_eq!(days.get(&date(2025, 11, 1)), Some(&(3 * 3600)));
+    }
+
+    #[test]
+    fn break_over_midnight_is_excluded_from_both_days() {
+        let s = session(
+            "2025-11-03T22:00:00Z",
+            "2025-11-04T02:00:00Z",
+            &[("2025-11-03T23:30:00Z", "2025-11-04T00:30:00Z")],
+        );
+        let days = seconds_per_day(&s, s.end.unwrap(), Tz::UTC);
+
+        assert_eq!(days.get(&date(2025, 11, 3)), Some(&(90 * 60)));
+        assert_eq!(days.get(&date(2025, 11, 4)), Some(&(90 * 60)));
+        assert_eq!(days.values().sum::<i64>(), s.calculate_total_seconds());
+    }
+
+    #[test]
+    fn splits_at_local_midnight() {
+        // 22:00-02:00 UTC is 23:00-03:00 in Berlin (CET)
+        let s = session("2025-11-03T22:00:00Z", "2025-11-04T02:00:00Z", &[]);
+        let days = seconds_per_day(&s, s.end.unwrap(), Berlin);
+
+        assert_eq!(days.get(&date(2025, 11, 3)), Some(&3600));
+        assert_eq!(days.get(&date(2025, 11, 4)), Some(&(3 * 3600)));
+    }
+
+    #[test]
+    fn overlapping_time_is_taken_once() {
+        let first = session("2025-11-03T09:00:00Z", "2025-11-03T12:00:00Z", &[]);
+        let second = session(
+            "2025-11-03T08:00:00Z",
+            "2025-11-03T14:00:00Z",
+            &[("2025-11-03T10:00:00Z", "2025-11-03T10:30:00Z")],
+        );
+        let mut covered = Vec::new();
+
+        let taken = take_uncovered(&worked_intervals(&first, first.end.unwrap()), &mut covered);
+        assert_eq!(taken.len(), 1);
+
+        let taken = take_uncovered(&worked_intervals(&second, second.end.unwrap()), &mut covered);
+        assert_eq!(
+            taken,
+            vec![
+                (utc("2025-11-03T08:00:00Z"), utc("2025-11-03T09:00:00Z")),
+                (utc("2025-11-03T12:00:00Z"), utc("2025-11-03T14:00:00Z")),
+            ]
+        );
+        assert_eq!(
+            covered,
+            vec![(utc("2025-11-03T08:00:00Z"), utc("2025-11-03T14:00:00Z"))]
+        );
+        assert_eq!(split_by_day(&taken, Tz::UTC).get(&date(2025, 11, 3)), Some(&(3 * 3600)));
+    }
+}
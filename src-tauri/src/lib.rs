@@ -1,15 +1,27 @@
 mod commands;
+mod error;
 mod excel_exporter;
+mod hotkeys;
+mod idle;
+mod intervals;
+mod local_time;
+mod lock;
+mod migrations;
 mod models;
 mod session_store;
 mod system_tray;
+#[cfg(test)]
+mod test_utils;
 mod timer_manager;
 
+use chrono::{DateTime, Utc};
 use commands::AppState;
 use session_store::SessionStore;
 use timer_manager::TimerManager;
+use models::{BudgetEvent, BudgetEventKind, BudgetExpiryAction, IdleEvent, PomodoroTransition};
 use std::sync::Arc;
-use tauri::Manager;
+use tauri::{AppHandle, Emitter, Manager, Runtime};
+use tauri_plugin_notification::NotificationExt;
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
@@ -17,6 +29,7 @@ pub fn run() {
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_notification::init())
+        .plugin(tauri_plugin_global_shortcut::Builder::new().build())
         .setup(|app| {
             // Get app data directory
             let app_dir = app
@@ -37,14 +50,72 @@ pub fn run() {
                 TimerManager::new(session_store.clone()).expect("Failed to initialize timer manager"),
             );
 
+            // Auto-pause when the user steps away and ask about the idle time on return
+            let app_handle = app.handle().clone();
+            idle::spawn_watcher(
+                idle::system_monitor(),
+                timer_manager.clone(),
+                session_store.clone(),
+                move |event| {
+                    let name = match event {
+                        IdleEvent::AutoPaused(_) => "idle-auto-paused",
+                        IdleEvent::Returned(_) => "idle-returned",
+                    };
+                    let _ = app_handle.emit(name, &event);
+                },
+            );
+
+            // Pause while the screen is locked
+            if let Some(monitor) = lock::system_monitor() {
+                lock::spawn_watcher(monitor, timer_manager.clone(), session_store.clone());
+            }
+
+            // Keep a heartbeat of the running session and flag time lost to sleep
+            let app_handle = app.handle().clone();
+            tauri::async_runtime::spawn(timer_manager.as_ref().clone().run_heartbeat(
+                timer_manager::HEARTBEAT_INTERVAL,
+                move |gap| {
+                    let _ = app_handle.emit("session-gap", &gap);
+                },
+            ));
+
+            // Drive Pomodoro phase changes, announcing each one
+            let app_handle = app.handle().clone();
+            tauri::async_runtime::spawn(timer_manager.as_ref().clone().run_pomodoro(
+                timer_manager::POMODORO_TICK,
+                move |transition| announce_pomodoro_phase(&app_handle, &transition),
+            ));
+
+            // Warn as session budgets run low and act when they run out
+            let app_handle = app.handle().clone();
+            tauri::async_runtime::spawn(timer_manager.as_ref().clone().run_budget_checks(
+                timer_manager::BUDGET_TICK,
+                move |event| announce_budget(&app_handle, &event),
+            ));
+
+            // Publish the elapsed time once a second while a timer runs
+            tauri::async_runtime::spawn(
+                timer_manager.as_ref().clone().run_ticks(timer_manager::EVENT_TICK),
+            );
+
             // Store state
+            let timer = timer_manager.as_ref().clone();
+            let hotkey_settings = session_store.get_hotkey_settings().unwrap_or_default();
             app.manage(AppState {
                 timer_manager,
                 session_store,
             });
 
-            // Create system tray
-            system_tray::create_tray(&app.handle())?;
+            // Register the global keyboard shortcuts for the timer
+            match hotkeys::register(app.handle(), &hotkey_settings) {
+                Ok(conflicts) => hotkeys::announce_conflicts(app.handle(), &conflicts),
+                Err(e) => eprintln!("Failed to register keyboard shortcuts: {}", e),
+            }
+
+            // Create system tray, kept in step with the timer from here on, and
+            // forward every timer event to the windows as `timer-event`
+            system_tray::create_tray(app.handle(), &timer.get_state())?;
+            tauri::async_runtime::spawn(system_tray::follow_timer(app.handle().clone(), timer));
 
             Ok(())
         })
@@ -54,13 +125,123 @@ pub fn run() {
             commands::resume_timer,
             commands::end_timer,
             commands::get_timer_state,
+            commands::start_named_timer,
+            commands::pause_named_timer,
+            commands::resume_named_timer,
+            commands::end_named_timer,
+            commands::list_timers,
+            commands::set_primary_timer,
             commands::get_current_elapsed,
+            commands::get_hotkey_settings,
+            commands::save_hotkey_settings,
+            commands::start_pomodoro,
+            commands::stop_pomodoro,
+            commands::get_pomodoro_settings,
+            commands::save_pomodoro_settings,
             commands::get_all_sessions,
+            commands::query_sessions,
+            commands::create_manual_session,
+            commands::update_session,
+            commands::delete_session,
+            commands::check_integrity,
+            commands::set_session_description,
+            commands::set_session_tags,
+            commands::get_all_tags,
+            commands::get_breaks,
             commands::get_monthly_summary,
+            commands::get_summary,
+            commands::get_daily_summary,
+            commands::get_weekly_summary,
+            commands::get_quarterly_summary,
+            commands::get_yearly_summary,
+            commands::get_range_summary,
+            commands::get_work_rules,
+            commands::save_work_rules,
+            commands::get_timezone,
+            commands::set_timezone,
+            commands::get_idle_settings,
+            commands::save_idle_settings,
+            commands::get_pending_idle,
+            commands::resolve_idle,
+            commands::get_pending_gaps,
+            commands::resolve_gap,
+            commands::get_all_clients,
+            commands::create_client,
+            commands::update_client,
+            commands::delete_client,
+            commands::get_all_projects,
+            commands::create_project,
+            commands::update_project,
+            commands::delete_project,
             commands::export_session,
             commands::export_monthly_summary,
-            commands::update_tray,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }
+
+fn announce_pomodoro_phase(app: &AppHandle, transition: &PomodoroTransition) {
+    let state = &transition.state;
+    let body = if state.phase.is_break() {
+        format!(
+            "Time for a {}. Back to work at {}.",
+            state.phase.label().to_lowercase(),
+            clock_time(app, state.phase_ends_at),
+        )
+    } else {
+        format!(
+            "Focus {} of {} until {}.",
+            state.cycle,
+            state.settings.cycles_before_long_break,
+            clock_time(app, state.phase_ends_at),
+        )
+    };
+
+    notify(app, state.phase.label(), body);
+    let _ = app.emit("pomodoro-phase", transition);
+}
+
+fn announce_budget(app: &AppHandle, event: &BudgetEvent) {
+    let minutes = |seconds: i64| (seconds + 59) / 60;
+    let (title, name) = match event.kind {
+        BudgetEventKind::Warning => ("Budget running low", "budget-warning"),
+        BudgetEventKind::Expired => ("Budget used up", "budget-expired"),
+    };
+    let body = match (event.kind, event.action) {
+        (BudgetEventKind::Warning, _) => format!(
+            "{} of {} minutes left.",
+            minutes(event.budget_seconds - event.elapsed_seconds),
+            minutes(event.budget_seconds),
+        ),
+        (BudgetEventKind::Expired, BudgetExpiryAction::Notify) => {
+            format!("The {} minutes are up.", minutes(event.budget_seconds))
+        }
+        (BudgetEventKind::Expired, BudgetExpiryAction::Pause) => format!(
+            "The {} minutes are up, so the timer was paused.",
+            minutes(event.budget_seconds)
+        ),
+        (BudgetEventKind::Expired, BudgetExpiryAction::End) => format!(
+            "The {} minutes are up, so the session was ended.",
+            minutes(event.budget_seconds)
+        ),
+    };
+
+    notify(app, title, body);
+    let _ = app.emit(name, event);
+}
+
+/// Format a time as the user's chosen time zone shows it on the clock
+pub(crate) fn clock_time<R: Runtime>(app: &AppHandle<R>, at: DateTime<Utc>) -> String {
+    let tz = app
+        .try_state::<AppState>()
+        .and_then(|state| state.session_store.get_timezone().ok())
+        .unwrap_or_else(local_time::system_timezone);
+    at.with_timezone(&tz).format("%H:%M").to_string()
+}
+
+/// Show a desktop notification, logging rather than failing if it can't be shown
+pub(crate) fn notify<R: Runtime>(app: &AppHandle<R>, title: &str, body: String) {
+    if let Err(e) = app.notification().builder().title(title).body(body).show() {
+        eprintln!("Failed to show notification: {}", e);
+    }
+}
@@ -0,0 +1,116 @@
+use crate::error::{OrError, Result, TimelyError};
+use chrono::{DateTime, Duration, LocalResult, NaiveDate, TimeZone, Utc};
+use chrono_tz::Tz;
+
+/// The system's IANA time zone, falling back to UTC if it can't be determined
+pub fn system_timezone() -> Tz {
+    iana_time_zone::get_timezone()
+        .ok()
+        .and_then(|name| name.parse().ok())
+        .unwrap_or(Tz::UTC)
+}
+
+pub fn parse_timezone(name: &str) -> Result<Tz> {
+    name.parse::<Tz>()
+        .map_err(|_| TimelyError::Validation(format!("Unknown time zone '{}'", name)))
+}
+
+/// The calendar date of `instant` as seen in `tz`
+pub fn local_date(tz: Tz, instant: DateTime<Utc>) -> NaiveDate {
+    instant.with_timezone(&tz).date_naive()
+}
+
+/// The instant the local day `date` begins in `tz`. Where a DST change skips
+/// midnight (e.g. America/Santiago), the day begins at the first valid time.
+pub fn start_of_day(tz: Tz, date: NaiveDate) -> DateTime<Utc> {
+    let mut local = date.and_hms_opt(0, 0, 0).unwrap();
+    loop {
+        match tz.from_local_datetime(&local) {
+            LocalResult::Single(dt) => return dt.with_timezone(&Utc),
+            // Midnight happens twice when clocks fall back across it; the day starts at the first
+            LocalResult::Ambiguous(earliest, _) => return earliest.with_timezone(&Utc),
+            // Gaps are at most a few hours; step forward until we're out of it
+            LocalResult::None => local += Duration::minutes(15),
+        }
+    }
+}
+
+/// The start of the local month and of the month after it, as UTC instants
+pub fn month_bounds(tz: Tz, year: i32, month: u32) -> Result<(DateTime<Utc>, DateTime<Utc>)> {
+    let first = NaiveDate::from_ymd_opt(year, month, 1)
+        .or_error(TimelyError::Validation, "Invalid date")?;
+    let next = if month == 12 {
+        NaiveDate::from_ymd_opt(year + 1, 1, 1)
+    } else {
+        NaiveDate::from_ymd_opt(year, month + 1, 1)
+    }
+    .or_error(TimelyError::Validation, "Invalid date")?;
+
+    Ok((start_of_day(tz, first), start_of_day(tz, next)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono_tz::America::{New_York, Santiago};
+    use chrono_tz::Europe::Berlin;
+
+    fn utc(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+    }
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn evening_session_stays_on_its_local_day() {
+        // 20:30 on Nov 3rd in New York is already Nov 4th in UTC
+        let start = utc("2025-11-04T01:30:00Z");
+        assert_eq!(local_date(New_York, start), date(2025, 11, 3));
+        assert_eq!(local_date(Tz::UTC, start), date(2025, 11, 4));
+    }
+
+    #[test]
+    fn month_bounds_follow_local_midnight() {
+        let (start, end) = month_bounds(New_York, 2025, 11).unwrap();
+        // EDT (UTC-4) on Nov 1st, EST (UTC-5) by Dec 1st
+        assert_eq!(start, utc("2025-11-01T04:00:00Z"));
+        assert_eq!(end, utc("2025-12-01T05:00:00Z"));
+    }
+
+    #[test]
+    fn spring_forward_day_is_23_hours() {
+        let day_start = start_of_day(Berlin, date(2025, 3, 30));
+        let next_start = start_of_day(Berlin, date(2025, 3, 31));
+        assert_eq!(next_start - day_start, Duration::hours(23));
+    }
+
+    #[test]
+    fn fall_back_day_is_25_hours() {
+        let day_start = start_of_day(Berlin, date(2025, 10, 26));
+        let next_start = start_of_day(Berlin, date(2025, 10, 27));
+        assert_eq!(next_start - day_start, Duration::hours(25));
+    }
+
+    #[test]
+    fn instants_either_side_of_a_dst_change_bucket_by_local_date() {
+        // 01:59 and 03:00 local on the night Berlin springs forward are one real minute apart
+        assert_eq!(local_date(Berlin, utc("2025-03-30T00:59:00Z")), date(2025, 3, 30));
+        assert_eq!(local_date(Berlin, utc("2025-03-30T01:00:00Z")), date(2025, 3, 30));
+        assert_eq!(local_date(Berlin, utc("2025-03-29T22:59:00Z")), date(2025, 3, 29));
+        assert_eq!(local_date(Berlin, utc("2025-03-29T23:00:00Z")), date(2025, 3, 30));
+    }
+
+    #[test]
+    fn day_starts_after_a_midnight_gap() {
+        // Chile springs forward at midnight, so Sep 7th 2025 starts at 01:00 local (UTC-3)
+        assert_eq!(start_of_day(Santiago, date(2025, 9, 7)), utc("2025-09-07T04:00:00Z"));
+    }
+
+    #[test]
+    fn parses_iana_names() {
+        assert_eq!(parse_timezone("Europe/Berlin").unwrap(), Berlin);
+        assert!(parse_timezone("Mars/Olympus_Mons").is_err());
+    }
+}
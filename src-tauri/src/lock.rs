@@ -0,0 +1,294 @@
+use crate::models::Session;
+use crate::session_store::SessionStore;
+use crate::timer_manager::TimerManager;
+use anyhow::Result;
+use std::sync::Arc;
+use std::thread;
+
+/// The screen locking or unlocking
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScreenLock {
+    Locked,
+    Unlocked,
+}
+
+/// A source of screen lock and unlock notifications
+pub trait LockMonitor: Send {
+    /// Block until the screen next locks or unlocks. An error means no more
+    /// changes will come.
+    fn next_change(&mut self) -> Result<ScreenLock>;
+}
+
+/// The lock monitor for the platform we're running on, if it has one
+pub fn system_monitor() -> Option<Box<dyn LockMonitor>> {
+    #[cfg(target_os = "linux")]
+    {
+        Some(Box::new(linux::DbusLockMonitor::new()))
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        None
+    }
+}
+
+/// Pause or resume the timer for a lock change, if the user wants that
+pub fn handle(
+    change: ScreenLock,
+    timer: &TimerManager,
+    store: &SessionStore,
+) -> Result<Option<Session>> {
+    let session = match change {
+        ScreenLock::Locked if store.get_idle_settings()?.pause_on_lock => timer.screen_locked()?,
+        ScreenLock::Locked => None,
+        // Always undo a lock pause, even if the setting was turned off meanwhile
+        ScreenLock::Unlocked => timer.screen_unlocked()?,
+    };
+    Ok(session)
+}
+
+/// Follow the monitor on a background thread for the lifetime of the app
+pub fn spawn_watcher(
+    mut monitor: Box<dyn LockMonitor>,
+    timer: Arc<TimerManager>,
+    store: Arc<SessionStore>,
+) {
+    thread::spawn(move || loop {
+        let change = match monitor.next_change() {
+            Ok(change) => change,
+            Err(e) => {
+                eprintln!("Stopped watching for screen locks: {}", e);
+                break;
+            }
+        };
+        if let Err(e) = handle(change, &timer, &store) {
+            eprintln!("Screen lock handling failed: {}", e);
+        }
+    });
+}
+
+#[cfg(target_os = "linux")]
+mod linux {
+    use super::{LockMonitor, ScreenLock};
+    use anyhow::{Context, Result};
+    use std::sync::mpsc::{self, Receiver, Sender};
+    use std::thread;
+    use zbus::blocking::{Connection, MessageIterator};
+    use zbus::zvariant::OwnedObjectPath;
+    use zbus::MatchRule;
+
+    /// Listens for the screen locking over D-Bus: logind's `Lock`/`Unlock`
+    /// signals for our login session on the system bus, and the screensaver's
+    /// `ActiveChanged` on the session bus (freedesktop and GNOME). Both may
+    /// report the same lock; handling is idempotent.
+    pub struct DbusLockMonitor {
+        changes: Receiver<ScreenLock>,
+    }
+
+    impl DbusLockMonitor {
+        pub fn new() -> Self {
+            let (tx, changes) = mpsc::channel();
+
+            let logind = tx.clone();
+            thread::spawn(move || {
+                if let Err(e) = Self::follow_logind(&logind) {
+                    eprintln!("Not following logind screen locks: {}", e);
+                }
+            });
+            thread::spawn(move || {
+                if let Err(e) = Self::follow_screensaver(&tx) {
+                    eprintln!("Not following screensaver locks: {}", e);
+                }
+            });
+
+            Self { changes }
+        }
+
+        fn follow_logind(tx: &Sender<ScreenLock>) -> Result<()> {
+            let bus = Connection::system()?;
+            let reply = bus.call_method(
+                Some("org.freedesktop.login1"),
+                "/org/freedesktop/login1",
+                Some("org.freedesktop.login1.Manager"),
+                "GetSessionByPID",
+                &(std::process::id()),
+            )?;
+            let session: OwnedObjectPath = reply.body().deserialize()?;
+            let rule = MatchRule::builder()
+                .msg_type(zbus::message::Type::Signal)
+                .interface("org.freedesktop.login1.Session")?
+                .path(session.as_str())?
+                .build();
+
+            for message in MessageIterator::for_match_rule(rule, &bus, None)? {
+                let message = message?;
+                let change = match message.header().member().map(|m| m.as_str()) {
+                    Some("Lock") => ScreenLock::Locked,
+                    Some("Unlock") => ScreenLock::Unlocked,
+                    _ => continue,
+                };
+                if tx.send(change).is_err() {
+                    break;
+                }
+            }
+            Ok(())
+        }
+
+        fn follow_screensaver(tx: &Sender<ScreenLock>) -> Result<()> {
+            let bus = Connection::session()?;
+            let rule = MatchRule::builder()
+                .msg_type(zbus::message::Type::Signal)
+                .member("ActiveChanged")?
+                .build();
+
+            for message in MessageIterator::for_match_rule(rule, &bus, None)? {
+                let message = message?;
+                let interface = message.header().interface().map(|i| i.to_string());
+                if !matches!(
+                    interface.as_deref(),
+                    Some("org.freedesktop.ScreenSaver" | "org.gnome.ScreenSaver")
+                ) {
+                    continue;
+                }
+                let active: bool = message.body().deserialize()?;
+                let change = if active {
+                    ScreenLock::Locked
+                } else {
+                    ScreenLock::Unlocked
+                };
+                if tx.send(change).is_err() {
+                    break;
+                }
+            }
+            Ok(())
+        }
+    }
+
+    impl LockMonitor for DbusLockMonitor {
+        fn next_change(&mut self) -> Result<ScreenLock> {
+            self.changes
+                .recv()
+                .context("No screen lock source is available")
+        }
+    }
+}
+
+/// Lock monitor replaying the changes a test queues up
+#[cfg(test)]
+#[derive(Default)]
+pub struct FakeLockMonitor {
+    changes: std::collections::VecDeque<ScreenLock>,
+}
+
+#[cfg(test)]
+impl FakeLockMonitor {
+    pub fn push(&mut self, change: ScreenLock) {
+        self.changes.push_back(change);
+    }
+}
+
+#[cfg(test)]
+impl LockMonitor for FakeLockMonitor {
+    fn next_change(&mut self) -> Result<ScreenLock> {
+        self.changes
+            .pop_front()
+            .ok_or_else(|| anyhow::anyhow!("No more lock changes"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::TempDir;
+    use crate::timer_manager::LOCK_BREAK_REASON;
+
+    struct Fixture {
+        _dir: TempDir,
+        store: Arc<SessionStore>,
+        timer: TimerManager,
+        monitor: FakeLockMonitor,
+    }
+
+    fn running() -> Fixture {
+        let dir = TempDir::new();
+        let store = Arc::new(SessionStore::new(dir.path().join("lock.db")).unwrap());
+        let timer = TimerManager::new(store.clone()).unwrap();
+        timer.start_session(None, None).unwrap();
+        Fixture {
+            _dir: dir,
+            store,
+            timer,
+            monitor: FakeLockMonitor::default(),
+        }
+    }
+
+    /// Feed the next queued change to the timer
+    fn step(f: &mut Fixture) -> Option<Session> {
+        let change = f.monitor.next_change().unwrap();
+        handle(change, &f.timer, &f.store).unwrap()
+    }
+
+    #[test]
+    fn lock_pauses_and_unlock_resumes() {
+        let mut f = running();
+        f.monitor.push(ScreenLock::Locked);
+        f.monitor.push(ScreenLock::Locked);
+        f.monitor.push(ScreenLock::Unlocked);
+        f.monitor.push(ScreenLock::Unlocked);
+
+        let session = step(&mut f).unwrap();
+        assert!(session.is_paused());
+        assert_eq!(session.breaks[0].reason.as_deref(), Some(LOCK_BREAK_REASON));
+
+        // A second source reporting the same lock changes nothing
+        assert!(step(&mut f).is_none());
+        assert_eq!(f.timer.get_current_session().unwrap().breaks.len(), 1);
+
+        let session = step(&mut f).unwrap();
+        assert!(!session.is_paused());
+        assert!(step(&mut f).is_none());
+
+        let stored = f.store.get_session(&session.id).unwrap().unwrap();
+        assert!(stored.breaks[0].ended_at.is_some());
+        assert!(f.monitor.next_change().is_err());
+    }
+
+    #[test]
+    fn unlock_leaves_manual_pauses_alone() {
+        let mut f = running();
+        f.timer.pause_session(Some("Lunch".into())).unwrap();
+        f.monitor.push(ScreenLock::Locked);
+        f.monitor.push(ScreenLock::Unlocked);
+
+        assert!(step(&mut f).is_none());
+        assert!(step(&mut f).is_none());
+        let session = f.timer.get_current_session().unwrap();
+        assert!(session.is_paused());
+        assert_eq!(session.breaks[0].reason.as_deref(), Some("Lunch"));
+    }
+
+    #[test]
+    fn unlock_after_manual_resume_does_nothing() {
+        let mut f = running();
+        f.monitor.push(ScreenLock::Locked);
+        f.monitor.push(ScreenLock::Unlocked);
+
+        step(&mut f).unwrap();
+        f.timer.resume_session().unwrap();
+        f.timer.pause_session(None).unwrap();
+
+        assert!(step(&mut f).is_none());
+        assert!(f.timer.get_current_session().unwrap().is_paused());
+    }
+
+    #[test]
+    fn lock_is_ignored_when_turned_off() {
+        let mut f = running();
+        let mut settings = f.store.get_idle_settings().unwrap();
+        settings.pause_on_lock = false;
+        f.store.save_idle_settings(&settings).unwrap();
+        f.monitor.push(ScreenLock::Locked);
+
+        assert!(step(&mut f).is_none());
+        assert!(!f.timer.get_current_session().unwrap().is_paused());
+    }
+}
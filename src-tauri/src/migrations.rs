@@ -0,0 +1,795 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use rusqlite::{params, Connection};
+use std::path::{Path, PathBuf};
+
+type Migration = fn(&Connection) -> Result<()>;
+
+/// Ordered schema migrations. Migration `i` upgrades the database from
+/// `user_version` `i` to `i + 1`; never reorder or edit an entry once shipped,
+/// only append new ones.
+const MIGRATIONS: &[Migration] = &[
+    create_sessions_table,
+    add_projects_and_clients,
+    add_descriptions_and_tags,
+    normalize_breaks,
+    add_session_indexes,
+    create_settings_table,
+    add_session_last_seen,
+    add_session_budgets,
+    add_session_timer_names,
+    normalize_timestamps_to_utc,
+];
+
+pub fn latest_version() -> u32 {
+    MIGRATIONS.len() as u32
+}
+
+/// Bring the database up to the latest schema version, backing up the file
+/// first if it already holds data.
+pub fn run_migrations(conn: &mut Connection, db_path: &Path) -> Result<()> {
+    let current = user_version(conn)?;
+    let latest = latest_version();
+
+    if current > latest {
+        anyhow::bail!(
+            "Database schema version {} is newer than this app supports ({})",
+            current,
+            latest
+        );
+    }
+    if current == latest {
+        return Ok(());
+    }
+
+    if has_existing_data(conn)? {
+        backup_database(conn, db_path, current)?;
+    }
+
+    for (index, migration) in MIGRATIONS.iter().enumerate().skip(current as usize) {
+        let version = index as u32 + 1;
+        let tx = conn.transaction()?;
+        migration(&tx).with_context(|| format!("Migration to version {} failed", version))?;
+        tx.pragma_update(None, "user_version", version)?;
+        tx.commit()
+            .with_context(|| format!("Failed to commit migration to version {}", version))?;
+    }
+
+    Ok(())
+}
+
+fn user_version(conn: &Connection) -> Result<u32> {
+    conn.query_row("PRAGMA user_version", [], |row| row.get(0))
+        .context("Failed to read schema version")
+}
+
+fn has_existing_data(conn: &Connection) -> Result<bool> {
+    let table_count: i64 = conn.query_row(
+        "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table'",
+        [],
+        |row| row.get(0),
+    )?;
+    Ok(table_count > 0)
+}
+
+/// Snapshot the database next to itself before migrating, e.g.
+/// `time-tracker.db` -> `time-tracker.v0-20250101T120000.bak.db`.
+/// `VACUUM INTO` rather than a file copy so pages still in the WAL are included.
+fn backup_database(conn: &Connection, db_path: &Path, version: u32) -> Result<PathBuf> {
+    let stem = db_path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("database");
+    let backup_path = db_path.with_file_name(format!(
+        "{}.v{}-{}.bak.db",
+        stem,
+        version,
+        Utc::now().format("%Y%m%dT%H%M%S")
+    ));
+
+    conn.execute(
+        "VACUUM INTO ?1",
+        params![backup_path.to_string_lossy().to_string()],
+    )
+    .with_context(|| format!("Failed to back up database to {}", backup_path.display()))?;
+
+    Ok(backup_path)
+}
+
+fn add_column_if_missing(conn: &Connection, table: &str, column: &str, definition: &str) -> Result<()> {
+    let exists = conn
+        .prepare(&format!(
+            "SELECT 1 FROM pragma_table_info('{}') WHERE name = ?1",
+            table
+        ))?
+        .exists(params![column])?;
+
+    if !exists {
+        conn.execute(
+            &format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition),
+            [],
+        )
+        .with_context(|| format!("Failed to add {} to {} table", column, table))?;
+    }
+
+    Ok(())
+}
+
+// Migrations 1-3 tolerate tables and columns that already exist, since
+// databases created before versioning have user_version 0 whatever their shape.
+
+fn create_sessions_table(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS sessions (
+            id TEXT PRIMARY KEY,
+            start TEXT NOT NULL,
+            pauses TEXT NOT NULL,
+            resumes TEXT NOT NULL,
+            end TEXT,
+            total_seconds INTEGER NOT NULL
+        )",
+        [],
+    )
+    .context("Failed to create sessions table")?;
+
+    Ok(())
+}
+
+fn add_projects_and_clients(conn: &Connection) -> Result<()> {
+    add_column_if_missing(conn, "sessions", "project_id", "TEXT")?;
+
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS clients (
+            id TEXT PRIMARY KEY,
+            name TEXT NOT NULL,
+            created_at TEXT NOT NULL
+        )",
+        [],
+    )
+    .context("Failed to create clients table")?;
+
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS projects (
+            id TEXT PRIMARY KEY,
+            name TEXT NOT NULL,
+            client_id TEXT REFERENCES clients(id),
+            created_at TEXT NOT NULL
+        )",
+        [],
+    )
+    .context("Failed to create projects table")?;
+
+    Ok(())
+}
+
+fn add_descriptions_and_tags(conn: &Connection) -> Result<()> {
+    add_column_if_missing(conn, "sessions", "description", "TEXT")?;
+
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS tags (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL UNIQUE COLLATE NOCASE
+        )",
+        [],
+    )
+    .context("Failed to create tags table")?;
+
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS session_tags (
+            session_id TEXT NOT NULL,
+            tag_id INTEGER NOT NULL REFERENCES tags(id),
+            PRIMARY KEY (session_id, tag_id)
+        )",
+        [],
+    )
+    .context("Failed to create session_tags table")?;
+
+    Ok(())
+}
+
+/// Move the parallel `pauses`/`resumes` JSON arrays into one `session_breaks`
+/// row per interval, pairing them by index as the old code did.
+fn normalize_breaks(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "CREATE TABLE session_breaks (
+            id TEXT PRIMARY KEY,
+            session_id TEXT NOT NULL REFERENCES sessions(id),
+            started_at TEXT NOT NULL,
+            ended_at TEXT,
+            reason TEXT
+        )",
+        [],
+    )
+    .context("Failed to create session_breaks table")?;
+
+    conn.execute(
+        "CREATE INDEX idx_session_breaks_session ON session_breaks (session_id, started_at)",
+        [],
+    )?;
+
+    // Pauses and resumes that can't be read are kept here rather than lost
+    // with their columns, for the integrity check to report
+    conn.execute(
+        "CREATE TABLE legacy_break_errors (
+            id INTEGER PRIMARY KEY,
+            session_id TEXT NOT NULL,
+            source_column TEXT NOT NULL,
+            position INTEGER,
+            value TEXT NOT NULL,
+            error TEXT NOT NULL
+        )",
+        [],
+    )
+    .context("Failed to create legacy_break_errors table")?;
+
+    let legacy: Vec<(String, String, String)> = conn
+        .prepare("SELECT id, pauses, resumes FROM sessions")?
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?
+        .collect::<Result<_, _>>()?;
+
+    let mut insert = conn.prepare(
+        "INSERT INTO session_breaks (id, session_id, started_at, ended_at) VALUES (?1, ?2, ?3, ?4)",
+    )?;
+    let mut keep_error = conn.prepare(
+        "INSERT INTO legacy_break_errors (session_id, source_column, position, value, error)
+         VALUES (?1, ?2, ?3, ?4, ?5)",
+    )?;
+    for (session_id, pauses_json, resumes_json) in legacy {
+        let mut errors = Vec::new();
+        let pauses = legacy_timestamps("pauses", &pauses_json, &mut errors);
+        let resumes = legacy_timestamps("resumes", &resumes_json, &mut errors);
+
+        // A pause that can't be read takes its resume with it; a resume that
+        // can't be read leaves its break open for the integrity check to close
+        for (i, pause) in pauses.iter().enumerate() {
+            let resume = resumes.get(i).copied().flatten();
+            let Some(pause) = pause else {
+                if let Some(resume) = resume {
+                    errors.push(LegacyBreakError {
+                        column: "resumes",
+                        position: Some(i),
+                        value: resume.to_rfc3339(),
+                        error: "Its pause couldn't be read".to_string(),
+                    });
+                }
+                continue;
+            };
+            insert.execute(params![
+                uuid::Uuid::new_v4().to_string(),
+                session_id,
+                pause.to_rfc3339(),
+                resume.map(|r| r.to_rfc3339()),
+            ])?;
+        }
+
+        // Resumes past the last pause have nothing to end
+        for (i, resume) in resumes.iter().enumerate().skip(pauses.len()) {
+            if let Some(resume) = resume {
+                errors.push(LegacyBreakError {
+                    column: "resumes",
+                    position: Some(i),
+                    value: resume.to_rfc3339(),
+                    error: "There's no pause for it".to_string(),
+                });
+            }
+        }
+
+        for error in errors {
+            keep_error.execute(params![
+                session_id,
+                error.column,
+                error.position,
+                error.value,
+                error.error,
+            ])?;
+        }
+    }
+
+    conn.execute("ALTER TABLE sessions DROP COLUMN pauses", [])?;
+    conn.execute("ALTER TABLE sessions DROP COLUMN resumes", [])?;
+
+    Ok(())
+}
+
+/// A legacy pause or resume that couldn't be moved into `session_breaks`
+struct LegacyBreakError {
+    column: &'static str,
+    /// Index in the JSON array, or None when the whole array was unreadable
+    position: Option<usize>,
+    value: String,
+    error: String,
+}
+
+/// Read one of the legacy JSON timestamp arrays, keeping each entry's
+/// position so pauses still pair with their resumes. Entries that can't be
+/// read are None and an array that can't be read at all is empty, with what
+/// was lost added to `errors`.
+fn legacy_timestamps(
+    column: &'static str,
+    json: &str,
+    errors: &mut Vec<LegacyBreakError>,
+) -> Vec<Option<DateTime<Utc>>> {
+    let entries: Vec<serde_json::Value> = match serde_json::from_str(json) {
+        Ok(entries) => entries,
+        Err(e) => {
+            errors.push(LegacyBreakError {
+                column,
+                position: None,
+                value: json.to_string(),
+                error: e.to_string(),
+            });
+            return Vec::new();
+        }
+    };
+
+    entries
+        .iter()
+        .enumerate()
+        .map(|(i, entry)| {
+            let at = entry
+                .as_str()
+                .and_then(|s| DateTime::parse_from_rfc3339(s).ok())
+                .map(|dt| dt.with_timezone(&Utc));
+            if at.is_none() {
+                errors.push(LegacyBreakError {
+                    column,
+                    position: Some(i),
+                    value: entry.to_string(),
+                    error: "Not an RFC 3339 timestamp".to_string(),
+                });
+            }
+            at
+        })
+        .collect()
+}
+
+/// Index the columns history queries filter and sort on
+fn add_session_indexes(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "CREATE INDEX idx_sessions_start ON sessions (start);
+         CREATE INDEX idx_sessions_end ON sessions (end);
+         CREATE INDEX idx_sessions_project ON sessions (project_id, start);
+         CREATE INDEX idx_session_tags_tag ON session_tags (tag_id);",
+    )
+    .context("Failed to create session indexes")?;
+
+    Ok(())
+}
+
+fn create_settings_table(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "CREATE TABLE settings (
+            key TEXT PRIMARY KEY,
+            value TEXT NOT NULL
+        )",
+        [],
+    )
+    .context("Failed to create settings table")?;
+
+    Ok(())
+}
+
+/// Heartbeat of the running session, so a session left open across a
+/// shutdown or sleep can be cut off where the app was last alive
+fn add_session_last_seen(conn: &Connection) -> Result<()> {
+    conn.execute("ALTER TABLE sessions ADD COLUMN last_seen_at TEXT", [])
+        .context("Failed to add last_seen_at column")?;
+
+    Ok(())
+}
+
+fn add_session_budgets(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "ALTER TABLE sessions ADD COLUMN budget_seconds INTEGER;
+         ALTER TABLE sessions ADD COLUMN budget_warning_seconds INTEGER;
+         ALTER TABLE sessions ADD COLUMN budget_on_expiry TEXT;",
+    )
+    .context("Failed to add budget columns")?;
+
+    Ok(())
+}
+
+fn add_session_timer_names(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "ALTER TABLE sessions ADD COLUMN timer_name TEXT NOT NULL DEFAULT 'Main'",
+        [],
+    )
+    .context("Failed to add timer_name column")?;
+
+    Ok(())
+}
+
+/// Rewrite timestamps stored with a local offset, as the session management
+/// script used to write them, in UTC the way the app writes them. Range
+/// queries compare timestamps as text, which only orders them correctly when
+/// they all share one offset.
+fn normalize_timestamps_to_utc(conn: &Connection) -> Result<()> {
+    let columns = [
+        ("sessions", "start"),
+        ("sessions", "end"),
+        ("sessions", "last_seen_at"),
+        ("session_breaks", "started_at"),
+        ("session_breaks", "ended_at"),
+        ("clients", "created_at"),
+        ("projects", "created_at"),
+    ];
+
+    for (table, column) in columns {
+        let stored: Vec<(String, String)> = conn
+            .prepare(&format!(
+                "SELECT id, {column} FROM {table} WHERE typeof({column}) = 'text'"
+            ))?
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<Result<_, _>>()?;
+
+        let mut update =
+            conn.prepare(&format!("UPDATE {table} SET {column} = ?1 WHERE id = ?2"))?;
+        for (id, value) in stored {
+            // Timestamps that can't be read are left for the integrity check
+            let Ok(at) = DateTime::parse_from_rfc3339(&value) else {
+                continue;
+            };
+            let utc = at.with_timezone(&Utc).to_rfc3339();
+            if utc != value {
+                update.execute(params![utc, id])?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::IntegrityIssueKind;
+    use crate::session_store::SessionStore;
+    use crate::test_utils::TempDir;
+
+    const SEED_DATA: &str = include_str!("../../seed_data.sql");
+
+    /// Sessions as the original release stored them, with pauses and resumes
+    /// as JSON arrays
+    const LEGACY_SEED_DATA: &str = "
+        INSERT INTO sessions (id, start, pauses, resumes, end, total_seconds) VALUES
+            ('seed-2025-11-01', '2025-11-01T10:47:00Z', '[]', '[]',
+             '2025-11-01T18:47:00Z', 28800),
+            ('seed-2024-11-18-morning', '2024-11-18T07:30:00Z', '[]', '[]',
+             '2024-11-18T10:00:00Z', 9000),
+            ('seed-2024-11-18-evening', '2024-11-18T17:00:00Z', '[]', '[]',
+             '2024-11-18T21:49:00Z', 17340);";
+
+    /// A database as created by the original release: unversioned, with only
+    /// the sessions table
+    fn legacy_fixture(path: &Path) {
+        let conn = Connection::open(path).unwrap();
+        create_sessions_table(&conn).unwrap();
+        conn.execute_batch(LEGACY_SEED_DATA).unwrap();
+    }
+
+    fn backups_in(dir: &Path) -> Vec<PathBuf> {
+        std::fs::read_dir(dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| path.to_string_lossy().ends_with(".bak.db"))
+            .collect()
+    }
+
+    fn column_exists(conn: &Connection, table: &str, column: &str) -> bool {
+        conn.prepare(&format!(
+            "SELECT 1 FROM pragma_table_info('{}') WHERE name = ?1",
+            table
+        ))
+        .unwrap()
+        .exists(params![column])
+        .unwrap()
+    }
+
+    #[test]
+    fn offset_timestamps_are_rewritten_in_utc() {
+        let dir = TempDir::new();
+        let db_path = dir.path().join("time-tracker.db");
+        legacy_fixture(&db_path);
+
+        let conn = Connection::open(&db_path).unwrap();
+        conn.execute(
+            "INSERT INTO sessions (id, start, pauses, resumes, end, total_seconds) VALUES (
+                'scripted', '2025-11-18T07:30:00-05:00', '[\"2025-11-18T08:00:00-05:00\"]',
+                '[\"2025-11-18T08:15:00-05:00\"]', '2025-11-18T10:00:00-05:00', 8100
+            )",
+            [],
+        )
+        .unwrap();
+        drop(conn);
+
+        let mut conn = Connection::open(&db_path).unwrap();
+        run_migrations(&mut conn, &db_path).unwrap();
+
+        let (start, end): (String, String) = conn
+            .query_row(
+                "SELECT start, end FROM sessions WHERE id = 'scripted'",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap();
+        assert_eq!(start, "2025-11-18T12:30:00+00:00");
+        assert_eq!(end, "2025-11-18T15:00:00+00:00");
+        let started_at: String = conn
+            .query_row(
+                "SELECT started_at FROM session_breaks WHERE session_id = 'scripted'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(started_at, "2025-11-18T13:00:00+00:00");
+
+        // Now a range on the stored text finds it in the right order
+        let ids: Vec<String> = conn
+            .prepare(
+                "SELECT id FROM sessions
+                 WHERE start >= '2025-11-18T12:00:00+00:00' ORDER BY start",
+            )
+            .unwrap()
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(ids, ["scripted"]);
+    }
+
+    #[test]
+    fn upgrades_legacy_database_to_latest() {
+        let dir = TempDir::new();
+        let db_path = dir.path().join("time-tracker.db");
+        legacy_fixture(&db_path);
+
+        let mut conn = Connection::open(&db_path).unwrap();
+        run_migrations(&mut conn, &db_path).unwrap();
+
+        assert_eq!(user_version(&conn).unwrap(), latest_version());
+        assert!(column_exists(&conn, "sessions", "project_id"));
+        assert!(column_exists(&conn, "sessions", "description"));
+
+        let count: i64 = conn
+            .query_row("SELECT COUNT(*) FROM sessions", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(count, 3);
+
+        let total: i64 = conn
+            .query_row(
+                "SELECT total_seconds FROM sessions WHERE id = 'seed-2025-11-01'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(total, 28800);
+    }
+
+    #[test]
+    fn moves_pauses_and_resumes_into_breaks() {
+        let dir = TempDir::new();
+        let db_path = dir.path().join("time-tracker.db");
+        legacy_fixture(&db_path);
+
+        let conn = Connection::open(&db_path).unwrap();
+        conn.execute(
+            "INSERT INTO sessions (id, start, pauses, resumes, end, total_seconds) VALUES (
+                'paused', '2025-11-03T09:00:00Z',
+                '[\"2025-11-03T10:00:00Z\",\"2025-11-03T12:00:00Z\"]',
+                '[\"2025-11-03T10:15:00Z\"]',
+                '2025-11-03T13:00:00Z', 9900
+            )",
+            [],
+        )
+        .unwrap();
+        drop(conn);
+
+        let mut conn = Connection::open(&db_path).unwrap();
+        run_migrations(&mut conn, &db_path).unwrap();
+
+        assert!(!column_exists(&conn, "sessions", "pauses"));
+        assert!(!column_exists(&conn, "sessions", "resumes"));
+
+        let breaks: Vec<(String, Option<String>)> = conn
+            .prepare(
+                "SELECT started_at, ended_at FROM session_breaks
+                 WHERE session_id = 'paused' ORDER BY started_at",
+            )
+            .unwrap()
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+
+        assert_eq!(
+            breaks,
+            vec![
+                (
+                    "2025-11-03T10:00:00+00:00".to_string(),
+                    Some("2025-11-03T10:15:00+00:00".to_string())
+                ),
+                ("2025-11-03T12:00:00+00:00".to_string(), None),
+            ]
+        );
+    }
+
+    #[test]
+    fn unreadable_pauses_are_kept_aside() {
+        let dir = TempDir::new();
+        let db_path = dir.path().join("time-tracker.db");
+        legacy_fixture(&db_path);
+
+        let conn = Connection::open(&db_path).unwrap();
+        conn.execute_batch(
+            "INSERT INTO sessions (id, start, pauses, resumes, end, total_seconds) VALUES (
+                'garbled', '2025-11-04T09:00:00Z', 'not json', '[]', '2025-11-04T10:00:00Z', 3600
+            );
+            INSERT INTO sessions (id, start, pauses, resumes, end, total_seconds) VALUES (
+                'half-read', '2025-11-05T09:00:00Z',
+                '[\"2025-11-05T10:00:00Z\",\"yesterday\"]',
+                '[\"10:15\",\"2025-11-05T11:00:00Z\"]',
+                '2025-11-05T13:00:00Z', 10800
+            );
+            INSERT INTO sessions (id, start, pauses, resumes, end, total_seconds) VALUES (
+                'extra-resume', '2025-11-06T09:00:00Z',
+                '[\"2025-11-06T10:00:00Z\"]',
+                '[\"2025-11-06T10:30:00Z\",\"2025-11-06T11:30:00Z\"]',
+                '2025-11-06T12:00:00Z', 9000
+            );",
+        )
+        .unwrap();
+        drop(conn);
+
+        let mut conn = Connection::open(&db_path).unwrap();
+        run_migrations(&mut conn, &db_path).unwrap();
+
+        let breaks: Vec<(String, String, Option<String>)> = conn
+            .prepare(
+                "SELECT session_id, started_at, ended_at FROM session_breaks
+                 WHERE session_id IN ('garbled', 'half-read', 'extra-resume')
+                 ORDER BY started_at",
+            )
+            .unwrap()
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(
+            breaks,
+            vec![
+                (
+                    "half-read".to_string(),
+                    "2025-11-05T10:00:00+00:00".to_string(),
+                    None
+                ),
+                (
+                    "extra-resume".to_string(),
+                    "2025-11-06T10:00:00+00:00".to_string(),
+                    Some("2025-11-06T10:30:00+00:00".to_string())
+                ),
+            ]
+        );
+
+        let kept: Vec<(String, String, Option<i64>, String)> = conn
+            .prepare(
+                "SELECT session_id, source_column, position, value FROM legacy_break_errors
+                 ORDER BY id",
+            )
+            .unwrap()
+            .query_map([], |row| {
+                Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?))
+            })
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        let kept: Vec<_> = kept
+            .iter()
+            .map(|(id, column, position, value)| {
+                (id.as_str(), column.as_str(), *position, value.as_str())
+            })
+            .collect();
+        assert_eq!(
+            kept,
+            [
+                ("garbled", "pauses", None, "not json"),
+                ("half-read", "pauses", Some(1), "\"yesterday\""),
+                ("half-read", "resumes", Some(0), "\"10:15\""),
+                ("half-read", "resumes", Some(1), "2025-11-05T11:00:00+00:00"),
+                (
+                    "extra-resume",
+                    "resumes",
+                    Some(1),
+                    "2025-11-06T11:30:00+00:00"
+                ),
+            ]
+        );
+        drop(conn);
+
+        let report = SessionStore::new(db_path)
+            .unwrap()
+            .check_integrity(false)
+            .unwrap();
+        let legacy = report
+            .issues
+            .iter()
+            .filter(|issue| issue.kind == IntegrityIssueKind::UnreadableLegacyBreak)
+            .count();
+        assert_eq!(legacy, 5);
+    }
+
+    #[test]
+    fn backs_up_existing_database_before_migrating() {
+        let dir = TempDir::new();
+        let db_path = dir.path().join("time-tracker.db");
+        legacy_fixture(&db_path);
+
+        let mut conn = Connection::open(&db_path).unwrap();
+        run_migrations(&mut conn, &db_path).unwrap();
+
+        let backups = backups_in(dir.path());
+        assert_eq!(backups.len(), 1);
+
+        // The backup is the untouched legacy database
+        let backup = Connection::open(&backups[0]).unwrap();
+        assert_eq!(user_version(&backup).unwrap(), 0);
+        assert!(!column_exists(&backup, "sessions", "project_id"));
+        assert!(column_exists(&backup, "sessions", "pauses"));
+    }
+
+    #[test]
+    fn seed_data_loads_into_a_migrated_database() {
+        let dir = TempDir::new();
+        let db_path = dir.path().join("time-tracker.db");
+
+        let mut conn = Connection::open(&db_path).unwrap();
+        run_migrations(&mut conn, &db_path).unwrap();
+        conn.execute_batch(SEED_DATA).unwrap();
+
+        let (sessions, breaks): (i64, i64) = conn
+            .query_row(
+                "SELECT (SELECT COUNT(*) FROM sessions), (SELECT COUNT(*) FROM session_breaks)",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap();
+        assert_eq!((sessions, breaks), (3, 1));
+    }
+
+    #[test]
+    fn fresh_database_is_not_backed_up() {
+        let dir = TempDir::new();
+        let db_path = dir.path().join("time-tracker.db");
+
+        let mut conn = Connection::open(&db_path).unwrap();
+        run_migrations(&mut conn, &db_path).unwrap();
+
+        assert_eq!(user_version(&conn).unwrap(), latest_version());
+        assert!(backups_in(dir.path()).is_empty());
+    }
+
+    #[test]
+    fn migrating_twice_is_a_no_op() {
+        let dir = TempDir::new();
+        let db_path = dir.path().join("time-tracker.db");
+        legacy_fixture(&db_path);
+
+        let mut conn = Connection::open(&db_path).unwrap();
+        run_migrations(&mut conn, &db_path).unwrap();
+        run_migrations(&mut conn, &db_path).unwrap();
+
+        assert_eq!(user_version(&conn).unwrap(), latest_version());
+        assert_eq!(backups_in(dir.path()).len(), 1);
+    }
+
+    #[test]
+    fn rejects_database_from_newer_version() {
+        let dir = TempDir::new();
+        let db_path = dir.path().join("time-tracker.db");
+
+        let mut conn = Connection::open(&db_path).unwrap();
+        conn.pragma_update(None, "user_version", latest_version() + 1)
+            .unwrap();
+
+        assert!(run_migrations(&mut conn, &db_path).is_err());
+    }
+}
@@ -1,44 +1,115 @@
-use chrono::{DateTime, Utc};
+use crate::error::{fail, OrError, Result, TimelyError};
+use chrono::{DateTime, Datelike, Months, NaiveDate, Utc, Weekday};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+/// Name of the timer sessions run on unless another is chosen
+pub const DEFAULT_TIMER_NAME: &str = "Main";
+
 /// Represents a time tracking session
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Session {
     pub id: String,
     pub start: DateTime<Utc>,
-    pub pauses: Vec<DateTime<Utc>>,
-    pub resumes: Vec<DateTime<Utc>>,
+    #[serde(default)]
+    pub breaks: Vec<Break>,
     pub end: Option<DateTime<Utc>>,
     pub total_seconds: i64,
+    pub project_id: Option<String>,
+    pub description: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// When the app last confirmed the session was running
+    #[serde(default)]
+    pub last_seen_at: Option<DateTime<Utc>>,
+    /// Time the session was boxed to when it started, if any
+    #[serde(default)]
+    pub budget: Option<SessionBudget>,
+    /// The timer the session ran on; several timers can run at once
+    #[serde(default = "Session::default_timer_name")]
+    pub timer_name: String,
 }
 
 impl Session {
-    pub fn new() -> Self {
+    pub fn new(project_id: Option<String>) -> Self {
         Self {
             id: Uuid::new_v4().to_string(),
             start: Utc::now(),
-            pauses: Vec::new(),
-            resumes: Vec::new(),
+            breaks: Vec::new(),
             end: None,
             total_seconds: 0,
+            project_id,
+            description: None,
+            tags: Vec::new(),
+            last_seen_at: None,
+            budget: None,
+            timer_name: DEFAULT_TIMER_NAME.to_string(),
         }
     }
 
-    /// Calculate total tracked seconds based on start, pauses, resumes, and end times
+    fn default_timer_name() -> String {
+        DEFAULT_TIMER_NAME.to_string()
+    }
+
+    /// Calculate total tracked seconds based on start, breaks, and end times
     pub fn calculate_total_seconds(&self) -> i64 {
         let end_time = self.end.unwrap_or_else(Utc::now);
-        let mut total = (end_time - self.start).num_seconds();
+        let total = (end_time - self.start).num_seconds();
+
+        (total - self.break_seconds()).max(0)
+    }
+
+    /// Total seconds spent on break, counting an open break up to the session end (or now)
+    pub fn break_seconds(&self) -> i64 {
+        let end_time = self.end.unwrap_or_else(Utc::now);
+        self.breaks
+            .iter()
+            .map(|b| (b.ended_at.unwrap_or(end_time) - b.started_at).num_seconds())
+            .sum()
+    }
+
+    /// Check that the session's timestamps are internally consistent: it has
+    /// ended after it started, and its breaks are in order, don't overlap and
+    /// fall within the session.
+    pub fn validate(&self) -> Result<()> {
+        let end = self
+            .end
+            .or_error(TimelyError::Validation, "Session must have an end time")?;
+
+        if self.start >= end {
+            fail!(Validation, "Session start must be before its end");
+        }
+        if end > Utc::now() {
+            fail!(Validation, "Session cannot end in the future");
+        }
 
-        // Subtract pause durations
-        for i in 0..self.pauses.len() {
-            let pause_start = self.pauses[i];
-            let pause_end = self.resumes.get(i).copied().unwrap_or(end_time);
-            let pause_duration = (pause_end - pause_start).num_seconds();
-            total -= pause_duration;
+        let mut previous = self.start;
+        for (i, b) in self.breaks.iter().enumerate() {
+            if b.started_at < previous {
+                fail!(
+                    Validation,
+                    "Break {} starts before the previous interval ended",
+                    i + 1
+                );
+            }
+            match b.ended_at {
+                Some(ended_at) if ended_at <= b.started_at => {
+                    fail!(Validation, "Break {} must end after it starts", i + 1);
+                }
+                Some(ended_at) => previous = ended_at,
+                // A session ended while paused leaves its last break open
+                None if i + 1 < self.breaks.len() => {
+                    fail!(Validation, "Only the last break may be left open");
+                }
+                None => previous = b.started_at,
+            }
         }
 
-        total.max(0)
+        if previous > end {
+            fail!(Validation, "Breaks must fall within the session");
+        }
+
+        Ok(())
     }
 
     /// Check if session is currently active (not ended)
@@ -48,18 +119,899 @@ impl Session {
 
     /// Check if session is currently paused
     pub fn is_paused(&self) -> bool {
-        self.is_active() && self.pauses.len() > self.resumes.len()
+        self.is_active() && self.breaks.last().is_some_and(|b| b.is_open())
+    }
+
+    /// Bring breaks written by older versions or by hand back in line with
+    /// `validate`: drop breaks that end before they start or fall outside the
+    /// session, close an open break at the next pause, and trim breaks to the
+    /// session and to each other. Returns one issue per break it changed.
+    pub fn repair_breaks(&mut self) -> Vec<IntegrityIssue> {
+        use IntegrityIssueKind::{MismatchedBreak, NegativeDuration};
+
+        let mut issues = Vec::new();
+        let mut problem = |b: &Break, kind: IntegrityIssueKind, message: &str| {
+            issues.push(IntegrityIssue::new(kind, "session_breaks", &b.id, message));
+        };
+
+        let mut breaks = std::mem::take(&mut self.breaks);
+        breaks.sort_by_key(|b| b.started_at);
+        breaks.retain(|b| {
+            let negative = b.ended_at.is_some_and(|ended_at| ended_at < b.started_at);
+            if negative {
+                problem(b, NegativeDuration, "Break ends before it starts");
+            }
+            !negative
+        });
+        let mut breaks = breaks.into_iter().peekable();
+        let mut repaired: Vec<Break> = Vec::new();
+
+        while let Some(mut b) = breaks.next() {
+            if b.is_open() {
+                if let Some(next) = breaks.peek() {
+                    problem(&b, MismatchedBreak, "Break was never resumed");
+                    b.ended_at = Some(next.started_at);
+                }
+            }
+
+            let outside = self.end.is_some_and(|end| b.started_at >= end)
+                || b.ended_at.is_some_and(|ended_at| ended_at <= self.start);
+            if outside {
+                problem(&b, MismatchedBreak, "Break falls outside its session");
+                continue;
+            }
+            if b.started_at < self.start {
+                problem(&b, MismatchedBreak, "Break starts before its session");
+                b.started_at = self.start;
+            }
+            if let (Some(end), Some(ended_at)) = (self.end, b.ended_at) {
+                if ended_at > end {
+                    problem(&b, MismatchedBreak, "Break ends after its session");
+                    b.ended_at = Some(end);
+                }
+            }
+
+            // Every break but the last has been closed above
+            if let Some(previous_end) = repaired.last().and_then(|p| p.ended_at) {
+                if b.ended_at.is_some_and(|ended_at| ended_at <= previous_end) {
+                    problem(&b, MismatchedBreak, "Break falls within another");
+                    continue;
+                }
+                if b.started_at < previous_end {
+                    problem(&b, MismatchedBreak, "Break overlaps the one before");
+                    b.started_at = previous_end;
+                }
+            }
+
+            repaired.push(b);
+        }
+
+        self.breaks = repaired;
+        issues
+    }
+}
+
+/// A time box for a session, e.g. "90 minutes on this ticket"
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SessionBudget {
+    /// Worked seconds the session is allowed
+    pub seconds: i64,
+    /// How long before the budget runs out to warn
+    #[serde(default = "SessionBudget::default_warning_seconds")]
+    pub warning_seconds: i64,
+    #[serde(default)]
+    pub on_expiry: BudgetExpiryAction,
+}
+
+impl SessionBudget {
+    fn default_warning_seconds() -> i64 {
+        5 * 60
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        if self.seconds <= 0 {
+            fail!(Validation, "Budget must be positive");
+        }
+        if self.warning_seconds < 0 || self.warning_seconds >= self.seconds {
+            fail!(
+                Validation,
+                "Budget warning must come before the budget runs out"
+            );
+        }
+        Ok(())
+    }
+}
+
+/// What the timer does when a session's budget runs out
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BudgetExpiryAction {
+    /// Only tell the user
+    #[default]
+    Notify,
+    Pause,
+    End,
+}
+
+impl BudgetExpiryAction {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            BudgetExpiryAction::Notify => "notify",
+            BudgetExpiryAction::Pause => "pause",
+            BudgetExpiryAction::End => "end",
+        }
+    }
+
+    pub fn parse(s: &str) -> Self {
+        match s {
+            "pause" => BudgetExpiryAction::Pause,
+            "end" => BudgetExpiryAction::End,
+            _ => BudgetExpiryAction::Notify,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BudgetEventKind {
+    /// The budget is about to run out
+    Warning,
+    /// The budget has run out; `action` has been applied
+    Expired,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BudgetEvent {
+    pub kind: BudgetEventKind,
+    pub session_id: String,
+    pub budget_seconds: i64,
+    pub elapsed_seconds: i64,
+    pub action: BudgetExpiryAction,
+}
+
+/// A paused interval within a session
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Break {
+    #[serde(default)]
+    pub id: String,
+    #[serde(default)]
+    pub session_id: String,
+    pub started_at: DateTime<Utc>,
+    pub ended_at: Option<DateTime<Utc>>,
+    pub reason: Option<String>,
+}
+
+impl Break {
+    pub fn new(session_id: &str, started_at: DateTime<Utc>) -> Self {
+        Self {
+            id: Uuid::new_v4().to_string(),
+            session_id: session_id.to_string(),
+            started_at,
+            ended_at: None,
+            reason: None,
+        }
+    }
+
+    /// Check if the break is still in progress
+    pub fn is_open(&self) -> bool {
+        self.ended_at.is_none()
+    }
+}
+
+/// Filters, ordering and paging for session history queries.
+/// Every field is optional; the default matches all sessions, newest first.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SessionQuery {
+    /// Only sessions starting at or after this instant
+    pub from: Option<DateTime<Utc>>,
+    /// Only sessions starting before this instant
+    pub to: Option<DateTime<Utc>>,
+    /// Bounds on the stored `total_seconds`, which is 0 for running sessions
+    pub min_duration_seconds: Option<i64>,
+    pub max_duration_seconds: Option<i64>,
+    pub status: Option<SessionStatus>,
+    pub project_id: Option<String>,
+    /// Only sessions carrying every one of these tags
+    pub tags: Vec<String>,
+    pub sort: SessionSort,
+    pub limit: Option<u32>,
+    pub offset: Option<u32>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SessionStatus {
+    Open,
+    Closed,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SessionSort {
+    #[default]
+    StartDesc,
+    StartAsc,
+    DurationDesc,
+    DurationAsc,
+}
+
+/// One page of a session query
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionPage {
+    pub sessions: Vec<Session>,
+    pub total_count: usize,
+    /// Offset of the next page, or None if this is the last one
+    pub next_offset: Option<u32>,
+    /// Matching rows left out because they couldn't be read
+    #[serde(default)]
+    pub skipped: Vec<SkippedRow>,
+}
+
+/// A stored row a list left out because it couldn't be read, e.g. one with
+/// a timestamp edited by hand. `check_integrity` finds and repairs these.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SkippedRow {
+    pub table: String,
+    pub id: String,
+    pub error: String,
+}
+
+/// The rows of a list that could be read, and the ones left out
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Rows<T> {
+    pub rows: Vec<T>,
+    #[serde(default)]
+    pub skipped: Vec<SkippedRow>,
+}
+
+/// Where a monthly export was written, and the rows it had to leave out
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MonthlyExport {
+    pub path: String,
+    #[serde(default)]
+    pub skipped: Vec<SkippedRow>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IntegrityIssueKind {
+    /// A timestamp that isn't RFC 3339
+    UnparsableTimestamp,
+    /// A pause without a matching resume, or a break overlapping another
+    /// or falling outside its session
+    MismatchedBreak,
+    /// A session or break that ends before it starts, or a negative total
+    NegativeDuration,
+    /// An ended session whose `total_seconds` disagrees with its timestamps
+    StaleTotal,
+    /// A pause or resume from before breaks had their own table that couldn't
+    /// be read when they were moved there. Kept in `legacy_break_errors` to
+    /// be fixed by hand.
+    UnreadableLegacyBreak,
+}
+
+/// Something wrong with one stored row
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IntegrityIssue {
+    pub kind: IntegrityIssueKind,
+    pub table: String,
+    pub id: String,
+    pub message: String,
+    /// Whether the check repaired it; only ever true when asked to fix
+    pub fixed: bool,
+}
+
+impl IntegrityIssue {
+    pub fn new(kind: IntegrityIssueKind, table: &str, id: &str, message: &str) -> Self {
+        Self {
+            kind,
+            table: table.to_string(),
+            id: id.to_string(),
+            message: message.to_string(),
+            fixed: false,
+        }
+    }
+}
+
+/// What an integrity check of the whole database found
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct IntegrityReport {
+    pub sessions_checked: usize,
+    pub breaks_checked: usize,
+    pub issues: Vec<IntegrityIssue>,
+}
+
+/// A client that projects are billed to
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Client {
+    pub id: String,
+    pub name: String,
+    pub created_at: DateTime<Utc>,
+}
+
+impl Client {
+    pub fn new(name: String) -> Self {
+        Self {
+            id: Uuid::new_v4().to_string(),
+            name,
+            created_at: Utc::now(),
+        }
+    }
+}
+
+/// A project that sessions can be attached to, optionally belonging to a client
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Project {
+    pub id: String,
+    pub name: String,
+    pub client_id: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl Project {
+    pub fn new(name: String, client_id: Option<String>) -> Self {
+        Self {
+            id: Uuid::new_v4().to_string(),
+            name,
+            client_id,
+            created_at: Utc::now(),
+        }
+    }
+}
+
+/// How worked hours split into regular time and overtime. Persisted in the
+/// settings table; every field has a default so older saved rules still load.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WorkRules {
+    /// Hours per week after which time counts as overtime
+    pub weekly_threshold_hours: f64,
+    /// Hours per day after which time counts as overtime, if the contract has a daily limit
+    pub daily_threshold_hours: Option<f64>,
+    /// First day of the working week, used to group days into weeks
+    pub week_start: Weekday,
+    /// Pay multiplier for hours over the weekly threshold
+    pub weekly_overtime_multiplier: f64,
+    /// Pay multiplier for hours over the daily threshold
+    pub daily_overtime_multiplier: f64,
+}
+
+impl Default for WorkRules {
+    fn default() -> Self {
+        Self {
+            weekly_threshold_hours: 40.0,
+            daily_threshold_hours: None,
+            week_start: Weekday::Mon,
+            weekly_overtime_multiplier: 1.5,
+            daily_overtime_multiplier: 1.5,
+        }
+    }
+}
+
+/// Regular and overtime hours for a period
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct OvertimeSplit {
+    pub regular_hours: f64,
+    pub overtime_hours: f64,
+    /// Hours weighted by the overtime multipliers, i.e. the paid equivalent
+    pub weighted_hours: f64,
+}
+
+impl WorkRules {
+    pub fn validate(&self) -> Result<()> {
+        if !(self.weekly_threshold_hours > 0.0 && self.weekly_threshold_hours <= 168.0) {
+            fail!(
+                Validation,
+                "Weekly threshold must be between 0 and 168 hours"
+            );
+        }
+        if let Some(daily) = self.daily_threshold_hours {
+            if !(daily > 0.0 && daily <= 24.0) {
+                fail!(Validation, "Daily threshold must be between 0 and 24 hours");
+            }
+        }
+        if self.weekly_overtime_multiplier < 1.0 || self.daily_overtime_multiplier < 1.0 {
+            fail!(Validation, "Overtime multipliers must be at least 1");
+        }
+        Ok(())
+    }
+
+    /// The first day of the week containing `date`
+    pub fn week_start_for(&self, date: NaiveDate) -> NaiveDate {
+        Granularity::Week.period_for(date, self.week_start).0
+    }
+
+    /// Split one week's worked hours, given per day, into regular and overtime.
+    /// Hours over the daily threshold are overtime first; the weekly threshold
+    /// then applies to what remains, so no hour is counted as overtime twice.
+    pub fn split_week(&self, daily_hours: &[f64]) -> OvertimeSplit {
+        let total: f64 = daily_hours.iter().sum();
+
+        let daily_overtime: f64 = match self.daily_threshold_hours {
+            Some(threshold) => daily_hours.iter().map(|h| (h - threshold).max(0.0)).sum(),
+            None => 0.0,
+        };
+        let weekly_overtime = (total - daily_overtime - self.weekly_threshold_hours).max(0.0);
+
+        let overtime_hours = daily_overtime + weekly_overtime;
+        let regular_hours = total - overtime_hours;
+
+        OvertimeSplit {
+            regular_hours,
+            overtime_hours,
+            weighted_hours: regular_hours
+                + daily_overtime * self.daily_overtime_multiplier
+                + weekly_overtime * self.weekly_overtime_multiplier,
+        }
+    }
+}
+
+/// When the timer pauses itself because the user stepped away. Persisted in
+/// the settings table.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct IdleSettings {
+    pub enabled: bool,
+    /// Minutes without keyboard or pointer input before the timer auto-pauses
+    pub threshold_minutes: u32,
+    /// Pause while the screen is locked and resume on unlock
+    pub pause_on_lock: bool,
+}
+
+impl Default for IdleSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            threshold_minutes: 5,
+            pause_on_lock: true,
+        }
+    }
+}
+
+impl IdleSettings {
+    pub fn validate(&self) -> Result<()> {
+        if !(1..=240).contains(&self.threshold_minutes) {
+            fail!(
+                Validation,
+                "Idle threshold must be between 1 and 240 minutes"
+            );
+        }
+        Ok(())
+    }
+
+    pub fn threshold(&self) -> chrono::Duration {
+        chrono::Duration::minutes(self.threshold_minutes as i64)
+    }
+}
+
+/// Idle time the timer was auto-paused for, waiting on the user to decide what it was
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IdleInterval {
+    pub session_id: String,
+    /// The open break recorded for the idle time
+    pub break_id: String,
+    /// Last input before the user went idle
+    pub started_at: DateTime<Utc>,
+    /// First input after the user came back, once they have
+    pub returned_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum IdleEvent {
+    /// The user went idle and the running session was paused
+    AutoPaused(IdleInterval),
+    /// The user is back; the interval needs a resolution
+    Returned(IdleInterval),
+}
+
+/// What to do with an idle interval once the user is back
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum IdleResolution {
+    /// Count the idle time as worked
+    Keep,
+    /// Leave the idle time out as a break
+    Discard,
+    /// Leave the idle time out as a break with the given reason, e.g. "Meeting"
+    Reassign { reason: String },
+}
+
+/// Time a running session went unobserved because the app was shut down or
+/// the machine slept, waiting on the user to decide what it was
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SessionGap {
+    pub session_id: String,
+    pub timer_name: String,
+    /// Last heartbeat before the gap
+    pub last_seen_at: DateTime<Utc>,
+    /// When the app noticed it was running again
+    pub detected_at: DateTime<Utc>,
+}
+
+/// What to do with a session gap
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum GapResolution {
+    /// End the session at the last heartbeat
+    EndAtLastSeen,
+    /// Keep the session running but record the gap as a break
+    InsertBreak,
+    /// Count the gap as worked
+    Keep,
+}
+
+/// Lengths of the Pomodoro phases. Persisted in the settings table.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PomodoroSettings {
+    pub focus_minutes: u32,
+    pub short_break_minutes: u32,
+    pub long_break_minutes: u32,
+    /// Focus phases per cycle; a long break follows the last one
+    pub cycles_before_long_break: u32,
+}
+
+impl Default for PomodoroSettings {
+    fn default() -> Self {
+        Self {
+            focus_minutes: 25,
+            short_break_minutes: 5,
+            long_break_minutes: 15,
+            cycles_before_long_break: 4,
+        }
+    }
+}
+
+impl PomodoroSettings {
+    pub fn validate(&self) -> Result<()> {
+        for (name, minutes) in [
+            ("Focus", self.focus_minutes),
+            ("Short break", self.short_break_minutes),
+            ("Long break", self.long_break_minutes),
+        ] {
+            if !(1..=240).contains(&minutes) {
+                fail!(
+                    Validation,
+                    "{} length must be between 1 and 240 minutes",
+                    name
+                );
+            }
+        }
+        if !(1..=12).contains(&self.cycles_before_long_break) {
+            fail!(
+                Validation,
+                "Cycles before a long break must be between 1 and 12"
+            );
+        }
+        Ok(())
+    }
+
+    pub fn length_of(&self, phase: PomodoroPhase) -> chrono::Duration {
+        let minutes = match phase {
+            PomodoroPhase::Focus => self.focus_minutes,
+            PomodoroPhase::ShortBreak => self.short_break_minutes,
+            PomodoroPhase::LongBreak => self.long_break_minutes,
+        };
+        chrono::Duration::minutes(minutes as i64)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PomodoroPhase {
+    Focus,
+    ShortBreak,
+    LongBreak,
+}
+
+impl PomodoroPhase {
+    pub fn label(self) -> &'static str {
+        match self {
+            PomodoroPhase::Focus => "Focus",
+            PomodoroPhase::ShortBreak => "Short break",
+            PomodoroPhase::LongBreak => "Long break",
+        }
+    }
+
+    pub fn is_break(self) -> bool {
+        self != PomodoroPhase::Focus
+    }
+}
+
+/// Where a running Pomodoro is in its cycle
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PomodoroState {
+    pub phase: PomodoroPhase,
+    /// Focus phase within the current cycle, starting at 1
+    pub cycle: u32,
+    pub phase_started_at: DateTime<Utc>,
+    pub phase_ends_at: DateTime<Utc>,
+    pub settings: PomodoroSettings,
+}
+
+/// A Pomodoro moving from one phase to the next
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PomodoroTransition {
+    pub from: PomodoroPhase,
+    pub state: PomodoroState,
+}
+
+/// Timer actions that can be bound to a global keyboard shortcut
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HotkeyAction {
+    Start,
+    Pause,
+    Resume,
+    End,
+}
+
+impl HotkeyAction {
+    pub const ALL: [HotkeyAction; 4] = [
+        HotkeyAction::Start,
+        HotkeyAction::Pause,
+        HotkeyAction::Resume,
+        HotkeyAction::End,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            HotkeyAction::Start => "Start",
+            HotkeyAction::Pause => "Pause",
+            HotkeyAction::Resume => "Resume",
+            HotkeyAction::End => "End",
+        }
     }
 }
 
-/// Weekly summary with overtime tracking
+/// Global keyboard shortcuts for the timer, written as accelerators such as
+/// "CmdOrCtrl+Alt+S". An action without one is left unbound. Persisted in
+/// the settings table.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HotkeySettings {
+    pub enabled: bool,
+    pub start: Option<String>,
+    pub pause: Option<String>,
+    pub resume: Option<String>,
+    pub end: Option<String>,
+}
+
+impl Default for HotkeySettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            start: Some("CmdOrCtrl+Alt+S".to_string()),
+            pause: Some("CmdOrCtrl+Alt+P".to_string()),
+            resume: Some("CmdOrCtrl+Alt+R".to_string()),
+            end: Some("CmdOrCtrl+Alt+E".to_string()),
+        }
+    }
+}
+
+impl HotkeySettings {
+    pub fn binding(&self, action: HotkeyAction) -> Option<&str> {
+        let binding = match action {
+            HotkeyAction::Start => &self.start,
+            HotkeyAction::Pause => &self.pause,
+            HotkeyAction::Resume => &self.resume,
+            HotkeyAction::End => &self.end,
+        };
+        binding.as_deref().map(str::trim).filter(|b| !b.is_empty())
+    }
+
+    /// The bound actions with their shortcuts
+    pub fn bindings(&self) -> Vec<(HotkeyAction, &str)> {
+        HotkeyAction::ALL
+            .into_iter()
+            .filter_map(|action| Some((action, self.binding(action)?)))
+            .collect()
+    }
+
+    /// Rejects one shortcut bound to two actions. Whether a shortcut is
+    /// well-formed and free is only known when it gets registered.
+    pub fn validate(&self) -> Result<()> {
+        let bindings = self.bindings();
+        for (i, (action, shortcut)) in bindings.iter().enumerate() {
+            let key = |s: &str| s.replace(' ', "").to_lowercase();
+            if let Some((other, _)) = bindings[..i].iter().find(|(_, s)| key(s) == key(shortcut)) {
+                fail!(
+                    Validation,
+                    "{} and {} can't both use {}",
+                    other.label(),
+                    action.label(),
+                    shortcut
+                );
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A shortcut that couldn't be registered, usually because another
+/// application already holds it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HotkeyConflict {
+    pub action: HotkeyAction,
+    pub shortcut: String,
+    pub message: String,
+}
+
+/// Weekly summary with overtime tracking. Covers the whole week, including
+/// days outside the period being summarized, since overtime applies per week.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WeeklySummary {
-    pub week_start: String,
-    pub week_end: String,
+    pub week_start: NaiveDate,
+    pub week_end: NaiveDate,
     pub regular_hours: f64,
     pub overtime_hours: f64,
+    pub weighted_hours: f64,
     pub total_hours: f64,
+    /// Hours worked on the days of this week that fall inside the summarized period
+    pub in_range_hours: f64,
+    pub session_count: usize,
+    pub days: Vec<WeekDaySummary>,
+}
+
+/// One day of a weekly summary
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WeekDaySummary {
+    pub date: NaiveDate,
+    pub total_seconds: i64,
+    pub in_range: bool,
+}
+
+/// The period a summary covers
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SummaryRange {
+    Day { date: NaiveDate },
+    /// ISO 8601 week, always starting on Monday
+    Week { year: i32, week: u32 },
+    Month { year: i32, month: u32 },
+    Quarter { year: i32, quarter: u32 },
+    Year { year: i32 },
+    /// Any span of days, both ends inclusive
+    Custom { start: NaiveDate, end: NaiveDate },
+}
+
+impl SummaryRange {
+    /// The first and last day of the range
+    pub fn dates(&self) -> Result<(NaiveDate, NaiveDate)> {
+        let (first, last) = match *self {
+            SummaryRange::Day { date } => (date, date),
+            SummaryRange::Week { year, week } => {
+                let first =
+                    NaiveDate::from_isoywd_opt(year, week, Weekday::Mon).ok_or_else(|| {
+                        TimelyError::Validation(format!("Invalid ISO week {} of {}", week, year))
+                    })?;
+                (first, first + chrono::Duration::days(6))
+            }
+            SummaryRange::Month { year, month } => {
+                let first = NaiveDate::from_ymd_opt(year, month, 1)
+                    .or_error(TimelyError::Validation, "Invalid date")?;
+                (first, last_day_after(first, 1)?)
+            }
+            SummaryRange::Quarter { year, quarter } => {
+                if !(1..=4).contains(&quarter) {
+                    fail!(Validation, "Quarter must be between 1 and 4");
+                }
+                let first = NaiveDate::from_ymd_opt(year, (quarter - 1) * 3 + 1, 1)
+                    .or_error(TimelyError::Validation, "Invalid date")?;
+                (first, last_day_after(first, 3)?)
+            }
+            SummaryRange::Year { year } => {
+                let first = NaiveDate::from_ymd_opt(year, 1, 1)
+                    .or_error(TimelyError::Validation, "Invalid date")?;
+                (first, last_day_after(first, 12)?)
+            }
+            SummaryRange::Custom { start, end } => {
+                if start > end {
+                    fail!(Validation, "Range start must not be after its end");
+                }
+                (start, end)
+            }
+        };
+        Ok((first, last))
+    }
+}
+
+/// The day before `first` plus `months` months
+fn last_day_after(first: NaiveDate, months: u32) -> Result<NaiveDate> {
+    first
+        .checked_add_months(Months::new(months))
+        .and_then(|next| next.pred_opt())
+        .or_error(TimelyError::Validation, "Invalid date")
+}
+
+/// How a summary's time is broken down into periods
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Granularity {
+    #[default]
+    Day,
+    Week,
+    Month,
+    Quarter,
+    Year,
+}
+
+impl Granularity {
+    /// The first and last day of the period containing `date`. Weeks begin on `week_start`.
+    pub fn period_for(self, date: NaiveDate, week_start: Weekday) -> (NaiveDate, NaiveDate) {
+        let first_of = |month: u32| NaiveDate::from_ymd_opt(date.year(), month, 1).unwrap();
+        let (first, months) = match self {
+            Granularity::Day => return (date, date),
+            Granularity::Week => {
+                let first = date
+                    - chrono::Duration::days(date.weekday().days_since(week_start) as i64);
+                return (first, first + chrono::Duration::days(6));
+            }
+            Granularity::Month => (first_of(date.month()), 1),
+            Granularity::Quarter => (first_of(date.month0() / 3 * 3 + 1), 3),
+            Granularity::Year => (first_of(1), 12),
+        };
+        (first, last_day_after(first, months).expect("date out of range"))
+    }
+}
+
+/// Time tracked within one period of a summary's breakdown
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PeriodSummary {
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+    pub total_seconds: i64,
+    pub session_count: usize,
+    /// The running session worked in this period, so its total is still growing
+    pub in_progress: bool,
+}
+
+/// Session summary for an arbitrary range of days
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Summary {
+    /// First day of the range
+    pub start: NaiveDate,
+    /// Last day of the range, inclusive
+    pub end: NaiveDate,
+    pub granularity: Granularity,
+    pub total_seconds: i64,
+    pub break_seconds: i64,
+    pub regular_hours: f64,
+    pub overtime_hours: f64,
+    pub weighted_hours: f64,
+    pub session_count: usize,
+    pub longest_session_seconds: i64,
+    /// Time per period at the requested granularity, for periods with any sessions
+    pub breakdown: Vec<PeriodSummary>,
+    pub weekly_breakdown: Vec<WeeklySummary>,
+    pub project_breakdown: Vec<ProjectSummary>,
+    /// How time tracked on several timers at once was counted
+    pub overlap: OverlapMode,
+    pub timer_breakdown: Vec<TimerSummary>,
+    /// Sessions, projects and clients left out because they couldn't be read
+    #[serde(default)]
+    pub skipped: Vec<SkippedRow>,
+}
+
+/// How a summary counts time tracked on several timers at once
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OverlapMode {
+    /// Each timer counts its own time, so an hour on two timers is two hours
+    #[default]
+    PerTimer,
+    /// Wall-clock time: an hour on two timers is one hour, credited to the
+    /// session that started first
+    Once,
+}
+
+/// Time tracked on a single timer within a summary's range
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimerSummary {
+    pub timer_name: String,
+    pub total_seconds: i64,
     pub session_count: usize,
 }
 
@@ -69,12 +1021,30 @@ pub struct MonthlySummary {
     pub year: i32,
     pub month: u32,
     pub total_seconds: i64,
+    pub break_seconds: i64,
     pub regular_hours: f64,
     pub overtime_hours: f64,
+    pub weighted_hours: f64,
     pub session_count: usize,
     pub longest_session_seconds: i64,
     pub daily_breakdown: Vec<DailySummary>,
     pub weekly_breakdown: Vec<WeeklySummary>,
+    pub project_breakdown: Vec<ProjectSummary>,
+    /// Sessions, projects and clients left out because they couldn't be read
+    #[serde(default)]
+    pub skipped: Vec<SkippedRow>,
+}
+
+/// Time tracked against a single project within a summary's range.
+/// Sessions without a project are grouped under `project_id: None`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectSummary {
+    pub project_id: Option<String>,
+    pub project_name: Option<String>,
+    pub client_id: Option<String>,
+    pub client_name: Option<String>,
+    pub total_seconds: i64,
+    pub session_count: usize,
 }
 
 /// Daily summary within a month
@@ -83,24 +1053,343 @@ pub struct DailySummary {
     pub date: String,
     pub total_seconds: i64,
     pub session_count: usize,
+    /// The running session worked on this day, so its total is still growing
+    pub in_progress: bool,
 }
 
 /// Timer state for the UI
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct TimerState {
     pub is_running: bool,
     pub is_paused: bool,
     pub current_session_id: Option<String>,
     pub elapsed_seconds: i64,
+    pub pomodoro: Option<PomodoroState>,
+    /// Every running timer, the primary first. The fields above describe the primary.
+    pub timers: Vec<RunningTimer>,
 }
 
-impl Default for TimerState {
-    fn default() -> Self {
+/// A timer with a session running on it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunningTimer {
+    pub name: String,
+    /// Whether this is the timer the window, tray and shortcuts act on, and
+    /// the one idle, lock, Pomodoro and budget handling apply to
+    pub primary: bool,
+    pub session_id: String,
+    pub project_id: Option<String>,
+    pub is_paused: bool,
+    pub elapsed_seconds: i64,
+}
+
+impl RunningTimer {
+    pub fn of(session: &Session, primary: bool) -> Self {
         Self {
-            is_running: false,
-            is_paused: false,
-            current_session_id: None,
-            elapsed_seconds: 0,
+            name: session.timer_name.clone(),
+            primary,
+            session_id: session.id.clone(),
+            project_id: session.project_id.clone(),
+            is_paused: session.is_paused(),
+            elapsed_seconds: session.calculate_total_seconds(),
+        }
+    }
+}
+
+/// A change to the timers, published by `TimerManager` as it happens.
+/// Session events carry the session as it is afterwards; its `timer_name`
+/// says which timer it was.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum TimerEvent {
+    Started { session: Session },
+    Paused { session: Session },
+    Resumed { session: Session },
+    Ended { session: Session },
+    /// Something else about the timers changed, e.g. a Pomodoro phase was
+    /// started or stopped or another timer became primary
+    Changed,
+    /// Sent every second while any timer is running
+    Tick { state: TimerState },
+}
+
+impl TimerEvent {
+    /// `Paused` or `Resumed`, whichever `session` now is
+    pub fn pause_change(session: &Session) -> Self {
+        let session = session.clone();
+        if session.is_paused() {
+            TimerEvent::Paused { session }
+        } else {
+            TimerEvent::Resumed { session }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn iso_week_starts_on_monday() {
+        // ISO week 1 of 2026 begins in December 2025
+        let range = SummaryRange::Week { year: 2026, week: 1 };
+        assert_eq!(range.dates().unwrap(), (date(2025, 12, 29), date(2026, 1, 4)));
+        assert!(SummaryRange::Week { year: 2025, week: 53 }.dates().is_err());
+    }
+
+    #[test]
+    fn quarter_and_year_cover_whole_months() {
+        let q1 = SummaryRange::Quarter { year: 2024, quarter: 1 };
+        assert_eq!(q1.dates().unwrap(), (date(2024, 1, 1), date(2024, 3, 31)));
+
+        let year = SummaryRange::Year { year: 2025 };
+        assert_eq!(year.dates().unwrap(), (date(2025, 1, 1), date(2025, 12, 31)));
+
+        assert!(SummaryRange::Quarter { year: 2025, quarter: 5 }.dates().is_err());
+    }
+
+    #[test]
+    fn custom_range_must_be_ordered() {
+        let range = SummaryRange::Custom { start: date(2025, 3, 2), end: date(2025, 3, 1) };
+        assert!(range.dates().is_err());
+    }
+
+    #[test]
+    fn periods_contain_the_date() {
+        let day = date(2025, 11, 13); // a Thursday
+        assert_eq!(
+            Granularity::Week.period_for(day, Weekday::Sun),
+            (date(2025, 11, 9), date(2025, 11, 15))
+        );
+        assert_eq!(
+            Granularity::Month.period_for(day, Weekday::Mon),
+            (date(2025, 11, 1), date(2025, 11, 30))
+        );
+        assert_eq!(
+            Granularity::Quarter.period_for(day, Weekday::Mon),
+            (date(2025, 10, 1), date(2025, 12, 31))
+        );
+    }
+
+    #[test]
+    fn hotkeys_reject_a_shortcut_bound_twice() {
+        let mut settings = HotkeySettings::default();
+        assert!(settings.validate().is_ok());
+        assert_eq!(settings.bindings().len(), 4);
+
+        settings.resume = Some("cmdorctrl + alt + p".to_string());
+        assert!(settings.validate().is_err());
+
+        settings.resume = Some("  ".to_string());
+        assert!(settings.validate().is_ok());
+        assert_eq!(settings.binding(HotkeyAction::Resume), None);
+    }
+
+    #[test]
+    fn repaired_breaks_pass_validation() {
+        let at = |hour: u32, minute: u32| {
+            date(2025, 3, 10)
+                .and_hms_opt(hour, minute, 0)
+                .unwrap()
+                .and_utc()
+        };
+        let mut session = Session::new(None);
+        session.start = at(9, 0);
+        session.end = Some(at(12, 0));
+        let mut add = |started: DateTime<Utc>, ended: Option<DateTime<Utc>>| {
+            let mut b = Break::new(&session.id, started);
+            b.ended_at = ended;
+            session.breaks.push(b);
+        };
+        add(at(8, 30), Some(at(9, 30)));
+        add(at(10, 0), None);
+        add(at(10, 30), Some(at(10, 15)));
+        add(at(10, 45), Some(at(11, 0)));
+        add(at(12, 30), Some(at(13, 0)));
+        assert!(session.validate().is_err());
+
+        let kinds: Vec<_> = session
+            .repair_breaks()
+            .into_iter()
+            .map(|i| i.kind)
+            .collect();
+        assert_eq!(
+            kinds,
+            [
+                IntegrityIssueKind::NegativeDuration,
+                IntegrityIssueKind::MismatchedBreak,
+                IntegrityIssueKind::MismatchedBreak,
+                IntegrityIssueKind::MismatchedBreak,
+            ]
+        );
+        assert!(session.validate().is_ok());
+
+        let kept: Vec<_> = session
+            .breaks
+            .iter()
+            .map(|b| (b.started_at, b.ended_at))
+            .collect();
+        assert_eq!(
+            kept,
+            [
+                (at(9, 0), Some(at(9, 30))),
+                (at(10, 0), Some(at(10, 45))),
+                (at(10, 45), Some(at(11, 0))),
+            ]
+        );
+    }
+
+    #[test]
+    fn session_validation_checks_times_and_breaks() {
+        let at =
+            |hour: u32, minute: u32| Utc.with_ymd_and_hms(2025, 3, 10, hour, minute, 0).unwrap();
+        let session = |start, end, breaks: &[(DateTime<Utc>, Option<DateTime<Utc>>)]| {
+            let mut session = Session::new(None);
+            session.start = start;
+            session.end = end;
+            for &(started_at, ended_at) in breaks {
+                let mut b = Break::new(&session.id, started_at);
+                b.ended_at = ended_at;
+                session.breaks.push(b);
+            }
+            session
+        };
+        let (nine, noon) = (at(9, 0), Some(at(12, 0)));
+
+        assert!(session(nine, noon, &[(at(10, 0), Some(at(10, 30)))])
+            .validate()
+            .is_ok());
+        // A session ended while paused keeps its last break open
+        assert!(session(nine, noon, &[(at(11, 0), None)]).validate().is_ok());
+
+        let invalid = [
+            session(nine, None, &[]),
+            session(nine, Some(nine), &[]),
+            session(nine, Some(at(8, 0)), &[]),
+            session(nine, Some(Utc::now() + chrono::Duration::hours(1)), &[]),
+            session(nine, noon, &[(at(10, 0), Some(at(10, 0)))]),
+            session(nine, noon, &[(at(8, 0), Some(at(9, 30)))]),
+            session(nine, noon, &[(at(11, 30), Some(at(12, 30)))]),
+            session(
+                nine,
+                noon,
+                &[
+                    (at(10, 0), Some(at(10, 30))),
+                    (at(10, 15), Some(at(10, 45))),
+                ],
+            ),
+            session(
+                nine,
+                noon,
+                &[(at(10, 0), None), (at(11, 0), Some(at(11, 15)))],
+            ),
+        ];
+        for (i, s) in invalid.iter().enumerate() {
+            assert_eq!(s.validate().unwrap_err().code(), "validation", "case {}", i);
+        }
+    }
+
+    fn split(regular_hours: f64, overtime_hours: f64, weighted_hours: f64) -> OvertimeSplit {
+        OvertimeSplit {
+            regular_hours,
+            overtime_hours,
+            weighted_hours,
+        }
+    }
+
+    #[test]
+    fn weekly_threshold_alone_splits_the_week() {
+        let rules = WorkRules::default();
+        assert_eq!(rules.split_week(&[8.0; 5]), split(40.0, 0.0, 40.0));
+        // Long days don't matter without a daily threshold, only the week's total
+        assert_eq!(
+            rules.split_week(&[12.0, 12.0, 12.0, 9.0]),
+            split(40.0, 5.0, 47.5)
+        );
+        assert_eq!(rules.split_week(&[]), split(0.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn daily_overtime_is_not_counted_again_for_the_week() {
+        let rules = WorkRules {
+            daily_threshold_hours: Some(8.0),
+            ..WorkRules::default()
+        };
+        // 10 hours over the daily limit leave exactly 40 for the week
+        assert_eq!(rules.split_week(&[10.0; 5]), split(40.0, 10.0, 55.0));
+        // A Saturday on top goes over the weekly threshold too
+        assert_eq!(
+            rules.split_week(&[10.0, 10.0, 10.0, 10.0, 10.0, 6.0]),
+            split(40.0, 16.0, 64.0)
+        );
+        // Short days under the daily limit still add up to weekly overtime
+        assert_eq!(rules.split_week(&[7.0; 7]), split(40.0, 9.0, 53.5));
+    }
+
+    #[test]
+    fn overtime_is_weighted_by_its_own_multiplier() {
+        let rules = WorkRules {
+            daily_threshold_hours: Some(8.0),
+            daily_overtime_multiplier: 2.0,
+            weekly_overtime_multiplier: 1.25,
+            ..WorkRules::default()
+        };
+        // 10 daily overtime hours at 2x, 8 weekly overtime hours at 1.25x
+        let week = rules.split_week(&[10.0, 10.0, 10.0, 10.0, 10.0, 8.0]);
+        assert_eq!(week, split(40.0, 18.0, 40.0 + 10.0 * 2.0 + 8.0 * 1.25));
+    }
+
+    #[test]
+    fn weeks_can_start_on_any_day() {
+        let monday = WorkRules::default();
+        let sunday = WorkRules {
+            week_start: Weekday::Sun,
+            ..WorkRules::default()
+        };
+        let saturday = WorkRules {
+            week_start: Weekday::Sat,
+            ..WorkRules::default()
+        };
+
+        // Wednesday 2025-03-12
+        assert_eq!(monday.week_start_for(date(2025, 3, 12)), date(2025, 3, 10));
+        assert_eq!(sunday.week_start_for(date(2025, 3, 12)), date(2025, 3, 9));
+        assert_eq!(saturday.week_start_for(date(2025, 3, 12)), date(2025, 3, 8));
+        // A Sunday starts its own week; the Saturday before ends the previous one
+        assert_eq!(sunday.week_start_for(date(2025, 3, 9)), date(2025, 3, 9));
+        assert_eq!(sunday.week_start_for(date(2025, 3, 8)), date(2025, 3, 2));
+        // Weeks run across month and year ends
+        assert_eq!(sunday.week_start_for(date(2026, 1, 1)), date(2025, 12, 28));
+    }
+
+    #[test]
+    fn work_rules_are_validated() {
+        assert!(WorkRules::default().validate().is_ok());
+
+        let invalid = [
+            WorkRules {
+                weekly_threshold_hours: 0.0,
+                ..WorkRules::default()
+            },
+            WorkRules {
+                weekly_threshold_hours: 200.0,
+                ..WorkRules::default()
+            },
+            WorkRules {
+                daily_threshold_hours: Some(25.0),
+                ..WorkRules::default()
+            },
+            WorkRules {
+                daily_overtime_multiplier: 0.5,
+                ..WorkRules::default()
+            },
+        ];
+        for rules in invalid {
+            assert_eq!(rules.validate().unwrap_err().code(), "validation");
         }
     }
 }
@@ -1,297 +1,2413 @@
-use crate::models::{DailySummary, MonthlySummary, Session};
-use anyhow::{Context, Result};
-use chrono::{DateTime, NaiveDate, Utc};
-use rusqlite::{params, Connection, OptionalExtension};
+use crate::error::{fail, OrError, Result, TimelyError};
+use crate::intervals;
+use crate::local_time;
+use crate::migrations;
+use crate::models::{
+    Break, BudgetExpiryAction, Client, DailySummary, Granularity, HotkeySettings, IdleSettings,
+    IntegrityIssue, IntegrityIssueKind, IntegrityReport, MonthlySummary, OverlapMode,
+    PeriodSummary, PomodoroSettings, Project, ProjectSummary, Rows, Session, SessionBudget,
+    SessionPage, SessionQuery, SessionSort, SessionStatus, SkippedRow, Summary, SummaryRange,
+    TimerSummary, WeekDaySummary, WeeklySummary, WorkRules,
+};
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+use chrono_tz::Tz;
+use rusqlite::types::{Type, Value, ValueRef};
+use rusqlite::{params, params_from_iter, Connection, OptionalExtension, Row, Transaction};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::path::PathBuf;
+use std::sync::{Mutex, MutexGuard};
+use std::time::Duration;
 
+const SESSION_COLUMNS: &str = "id, start, end, total_seconds, project_id, description, \
+     last_seen_at, budget_seconds, budget_warning_seconds, budget_on_expiry, timer_name";
+
+/// Why a stored value couldn't be read, e.g. a timestamp edited by hand
+#[derive(Debug, thiserror::Error)]
+#[error("{column} {value:?} is not a valid timestamp")]
+struct BadTimestamp {
+    column: String,
+    value: String,
+}
+
+fn parse_timestamp(s: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(s)
+        .ok()
+        .map(|dt| dt.with_timezone(&Utc))
+}
+
+/// Read a timestamp in SQLite's own `YYYY-MM-DD HH:MM:SS` format, which
+/// `datetime()` and `CURRENT_TIMESTAMP` write in UTC
+fn parse_sqlite_timestamp(s: &str) -> Option<DateTime<Utc>> {
+    ["%Y-%m-%d %H:%M:%S%.f", "%Y-%m-%dT%H:%M:%S%.f"]
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(s, format).ok())
+        .map(|dt| dt.and_utc())
+}
+
+/// A column as text whatever SQLite actually stored, for checking values
+/// that may not be what the schema expects
+fn raw_text_at(row: &Row, idx: usize) -> rusqlite::Result<Option<String>> {
+    Ok(match row.get_ref(idx)? {
+        ValueRef::Null => None,
+        ValueRef::Integer(i) => Some(i.to_string()),
+        ValueRef::Real(f) => Some(f.to_string()),
+        ValueRef::Text(bytes) | ValueRef::Blob(bytes) => {
+            Some(String::from_utf8_lossy(bytes).into_owned())
+        }
+    })
+}
+
+/// Read a timestamp column, failing the row rather than the whole query when
+/// it doesn't parse
+fn timestamp_at(row: &Row, idx: usize) -> rusqlite::Result<DateTime<Utc>> {
+    let value: String = row.get(idx)?;
+    parse_timestamp(&value).ok_or_else(|| {
+        let column = row
+            .as_ref()
+            .column_name(idx)
+            .unwrap_or("timestamp")
+            .to_string();
+        rusqlite::Error::FromSqlConversionFailure(
+            idx,
+            Type::Text,
+            Box::new(BadTimestamp { column, value }),
+        )
+    })
+}
+
+fn optional_timestamp_at(row: &Row, idx: usize) -> rusqlite::Result<Option<DateTime<Utc>>> {
+    match row.get_ref(idx)? {
+        ValueRef::Null => Ok(None),
+        _ => timestamp_at(row, idx).map(Some),
+    }
+}
+
+/// Whether a row failed to read because of what's stored in it, as opposed
+/// to the database itself failing
+fn is_bad_row(e: &rusqlite::Error) -> bool {
+    matches!(
+        e,
+        rusqlite::Error::FromSqlConversionFailure(..) | rusqlite::Error::InvalidColumnType(..)
+    )
+}
+
+/// What's wrong with a row that failed to read
+fn row_error_message(e: &rusqlite::Error) -> String {
+    match e {
+        rusqlite::Error::FromSqlConversionFailure(_, _, inner) => inner.to_string(),
+        other => other.to_string(),
+    }
+}
+
+fn skipped_row(table: &str, id: &str, e: &rusqlite::Error) -> SkippedRow {
+    SkippedRow {
+        table: table.to_string(),
+        id: id.to_string(),
+        error: row_error_message(e),
+    }
+}
+
+/// The error for a single row that can't be read
+fn unreadable(table: &str, id: &str, e: rusqlite::Error) -> TimelyError {
+    if is_bad_row(&e) {
+        TimelyError::Storage(format!(
+            "Can't read {} row {}: {}",
+            table,
+            id,
+            row_error_message(&e)
+        ))
+    } else {
+        e.into()
+    }
+}
+
+/// Pair each row read by `read` with the row's id (its first column), so a
+/// row that fails to read can still be reported
+fn with_id<T>(
+    read: impl Fn(&Row) -> rusqlite::Result<T>,
+) -> impl FnMut(&Row) -> rusqlite::Result<(String, rusqlite::Result<T>)> {
+    move |row| Ok((row.get(0)?, read(row)))
+}
+
+/// Collect the rows that could be read, leaving out and noting in `skipped`
+/// the ones that couldn't so one bad row doesn't hide all the others
+fn readable_rows<T>(
+    rows: impl Iterator<Item = rusqlite::Result<(String, rusqlite::Result<T>)>>,
+    table: &str,
+    skipped: &mut Vec<SkippedRow>,
+) -> rusqlite::Result<Vec<T>> {
+    let mut readable = Vec::new();
+    for row in rows {
+        match row? {
+            (_, Ok(value)) => readable.push(value),
+            (id, Err(e)) if is_bad_row(&e) => skipped.push(skipped_row(table, &id, &e)),
+            (_, Err(e)) => return Err(e),
+        }
+    }
+    Ok(readable)
+}
+
+/// Fill in the breaks and tags of each session, leaving out sessions with
+/// breaks that can't be read
+fn with_details(
+    conn: &Connection,
+    sessions: Vec<Session>,
+    skipped: &mut Vec<SkippedRow>,
+) -> rusqlite::Result<Vec<Session>> {
+    let mut complete = Vec::with_capacity(sessions.len());
+    for mut session in sessions {
+        match load_details(conn, &mut session) {
+            Ok(()) => complete.push(session),
+            Err(e) if is_bad_row(&e) => skipped.push(skipped_row("sessions", &session.id, &e)),
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(complete)
+}
+
+fn session_from_row(row: &Row) -> rusqlite::Result<Session> {
+    Ok(Session {
+        id: row.get(0)?,
+        start: timestamp_at(row, 1)?,
+        breaks: Vec::new(),
+        end: optional_timestamp_at(row, 2)?,
+        total_seconds: row.get(3)?,
+        project_id: row.get(4)?,
+        description: row.get(5)?,
+        tags: Vec::new(),
+        last_seen_at: optional_timestamp_at(row, 6)?,
+        budget: match row.get::<_, Option<i64>>(7)? {
+            Some(seconds) => Some(SessionBudget {
+                seconds,
+                warning_seconds: row.get::<_, Option<i64>>(8)?.unwrap_or(0),
+                on_expiry: row
+                    .get::<_, Option<String>>(9)?
+                    .map(|s| BudgetExpiryAction::parse(&s))
+                    .unwrap_or_default(),
+            }),
+            None => None,
+        },
+        timer_name: row.get(10)?,
+    })
+}
+
+/// One pass of `SessionStore::check_integrity`, collecting what it finds
+struct IntegrityCheck<'a> {
+    tx: &'a Transaction<'a>,
+    fix: bool,
+    issues: Vec<IntegrityIssue>,
+}
+
+impl IntegrityCheck<'_> {
+    /// Note a problem, applying `repair` to it when fixing
+    fn repair(
+        &mut self,
+        mut issue: IntegrityIssue,
+        repair: impl FnOnce(&Transaction) -> rusqlite::Result<usize>,
+    ) -> Result<()> {
+        if self.fix {
+            repair(self.tx)?;
+            issue.fixed = true;
+        }
+        self.issues.push(issue);
+        Ok(())
+    }
+
+    /// Check a stored timestamp and return the time it holds, if any.
+    /// Timestamps in SQLite's own format are rewritten as RFC 3339; ones that
+    /// can't be read at all are cleared if `clearable`, else only reported.
+    fn timestamp(
+        &mut self,
+        table: &str,
+        column: &str,
+        id: &str,
+        value: &str,
+        clearable: bool,
+    ) -> Result<Option<DateTime<Utc>>> {
+        if let Some(at) = parse_timestamp(value) {
+            return Ok(Some(at));
+        }
+
+        let message = BadTimestamp {
+            column: column.to_string(),
+            value: value.to_string(),
+        }
+        .to_string();
+        let issue =
+            IntegrityIssue::new(IntegrityIssueKind::UnparsableTimestamp, table, id, &message);
+        let update = format!("UPDATE {} SET {} = ?1 WHERE id = ?2", table, column);
+
+        let at = parse_sqlite_timestamp(value);
+        match at {
+            Some(at) => self.repair(issue, |tx| {
+                tx.execute(&update, params![at.to_rfc3339(), id])
+            })?,
+            None if clearable => {
+                self.repair(issue, |tx| tx.execute(&update, params![None::<String>, id]))?
+            }
+            None => self.issues.push(issue),
+        }
+        Ok(at)
+    }
+}
+
+const BREAK_COLUMNS: &str = "id, session_id, started_at, ended_at, reason";
+
+fn break_from_row(row: &Row) -> rusqlite::Result<Break> {
+    Ok(Break {
+        id: row.get(0)?,
+        session_id: row.get(1)?,
+        started_at: timestamp_at(row, 2)?,
+        ended_at: optional_timestamp_at(row, 3)?,
+        reason: row.get(4)?,
+    })
+}
+
+/// Fill in a session's breaks and tags from their own tables
+fn load_details(conn: &Connection, session: &mut Session) -> rusqlite::Result<()> {
+    load_breaks(conn, session)?;
+    load_tags(conn, session)
+}
+
+fn load_breaks(conn: &Connection, session: &mut Session) -> rusqlite::Result<()> {
+    let mut stmt = conn.prepare_cached(&format!(
+        "SELECT {} FROM session_breaks WHERE session_id = ?1 ORDER BY started_at ASC",
+        BREAK_COLUMNS
+    ))?;
+
+    session.breaks = stmt
+        .query_map(params![session.id], break_from_row)?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(())
+}
+
+/// Store `session.breaks` in place of the session's current ones
+fn replace_breaks(conn: &Connection, session: &Session) -> rusqlite::Result<()> {
+    conn.prepare_cached("DELETE FROM session_breaks WHERE session_id = ?1")?
+        .execute(params![session.id])?;
+
+    let mut insert = conn.prepare_cached(
+        "INSERT INTO session_breaks (id, session_id, started_at, ended_at, reason)
+         VALUES (?1, ?2, ?3, ?4, ?5)",
+    )?;
+    for b in &session.breaks {
+        insert.execute(params![
+            b.id,
+            session.id,
+            b.started_at.to_rfc3339(),
+            b.ended_at.map(|dt| dt.to_rfc3339()),
+            b.reason,
+        ])?;
+    }
+
+    Ok(())
+}
+
+/// Fill in `session.tags` from the session_tags join table
+fn load_tags(conn: &Connection, session: &mut Session) -> rusqlite::Result<()> {
+    let mut stmt = conn.prepare_cached(
+        "SELECT t.name FROM session_tags st
+         JOIN tags t ON t.id = st.tag_id
+         WHERE st.session_id = ?1
+         ORDER BY t.name COLLATE NOCASE",
+    )?;
+
+    session.tags = stmt
+        .query_map(params![session.id], |row| row.get(0))?
+        .collect::<Result<Vec<String>, _>>()?;
+
+    Ok(())
+}
+
+/// Trim whitespace and a leading '#' so "#code-review" and "code-review" are the same tag
+fn normalize_tag(tag: &str) -> String {
+    tag.trim().trim_start_matches('#').trim().to_string()
+}
+
+/// Normalize, drop empty and case-insensitively de-duplicate a list of tags
+pub fn normalize_tags(tags: &[String]) -> Vec<String> {
+    let mut normalized: Vec<String> = Vec::new();
+    for tag in tags {
+        let name = normalize_tag(tag);
+        if !name.is_empty() && !normalized.iter().any(|t| t.eq_ignore_ascii_case(&name)) {
+            normalized.push(name);
+        }
+    }
+    normalized.sort_by_key(|t| t.to_lowercase());
+    normalized
+}
+
+fn client_from_row(row: &Row) -> rusqlite::Result<Client> {
+    Ok(Client {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        created_at: timestamp_at(row, 2)?,
+    })
+}
+
+fn project_from_row(row: &Row) -> rusqlite::Result<Project> {
+    Ok(Project {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        client_id: row.get(2)?,
+        created_at: timestamp_at(row, 3)?,
+    })
+}
+
+/// How long a statement waits on a lock held by another connection (e.g. the
+/// session management scripts) before failing with SQLITE_BUSY
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+const STATEMENT_CACHE_CAPACITY: usize = 64;
+
+const WORK_RULES_KEY: &str = "work_rules";
+const TIMEZONE_KEY: &str = "timezone";
+const IDLE_SETTINGS_KEY: &str = "idle";
+const POMODORO_SETTINGS_KEY: &str = "pomodoro";
+const HOTKEY_SETTINGS_KEY: &str = "hotkeys";
+const PRIMARY_TIMER_KEY: &str = "primary_timer";
+
+/// SQLite-backed storage. Holds a single long-lived connection; callers are
+/// serialized on it, which is plenty for one desktop user.
 pub struct SessionStore {
-    db_path: PathBuf,
+    conn: Mutex<Connection>,
 }
 
 impl SessionStore {
     pub fn new(db_path: PathBuf) -> Result<Self> {
-        let store = Self { db_path };
-        store.init_database()?;
-        Ok(store)
+        let mut conn = Connection::open(&db_path)
+            .or_error(TimelyError::Storage, "Failed to open database connection")?;
+        Self::configure_connection(&conn)?;
+        migrations::run_migrations(&mut conn, &db_path)
+            .or_error(TimelyError::Storage, "Failed to migrate database")?;
+
+        Ok(Self {
+            conn: Mutex::new(conn),
+        })
     }
 
-    fn get_connection(&self) -> Result<Connection> {
-        Connection::open(&self.db_path).context("Failed to open database connection")
+    fn configure_connection(conn: &Connection) -> Result<()> {
+        conn.pragma_update(None, "journal_mode", "WAL")
+            .or_error(TimelyError::Storage, "Failed to enable WAL journal mode")?;
+        // NORMAL is durable across application crashes in WAL mode and avoids an fsync per commit
+        conn.pragma_update(None, "synchronous", "NORMAL")?;
+        conn.busy_timeout(BUSY_TIMEOUT)?;
+        conn.set_prepared_statement_cache_capacity(STATEMENT_CACHE_CAPACITY);
+        Ok(())
     }
 
-    fn init_database(&self) -> Result<()> {
-        let conn = self.get_connection()?;
+    fn get_connection(&self) -> Result<MutexGuard<'_, Connection>> {
+        self.conn
+            .lock()
+            .map_err(|_| TimelyError::Storage("Database connection lock was poisoned".to_string()))
+    }
 
-        conn.execute(
-            "CREATE TABLE IF NOT EXISTS sessions (
-                id TEXT PRIMARY KEY,
-                start TEXT NOT NULL,
-                pauses TEXT NOT NULL,
-                resumes TEXT NOT NULL,
-                end TEXT,
-                total_seconds INTEGER NOT NULL
-            )",
-            [],
-        )
-        .context("Failed to create sessions table")?;
+    pub fn save_session(&self, session: &Session) -> Result<()> {
+        let mut conn = self.get_connection()?;
+        let tx = conn.transaction()?;
+
+        let end_str = session.end.map(|dt| dt.to_rfc3339());
+
+        tx.prepare_cached(
+            "INSERT OR REPLACE INTO sessions
+                 (id, start, end, total_seconds, project_id, description, last_seen_at,
+                  budget_seconds, budget_warning_seconds, budget_on_expiry, timer_name)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+        )?
+        .execute(params![
+            session.id,
+            session.start.to_rfc3339(),
+            end_str,
+            session.total_seconds,
+            session.project_id,
+            session.description,
+            session.last_seen_at.map(|dt| dt.to_rfc3339()),
+            session.budget.as_ref().map(|b| b.seconds),
+            session.budget.as_ref().map(|b| b.warning_seconds),
+            session.budget.as_ref().map(|b| b.on_expiry.as_str()),
+            session.timer_name,
+        ])
+        .or_error(TimelyError::Storage, "Failed to save session")?;
+
+        replace_breaks(&tx, session)?;
+
+        tx.prepare_cached("DELETE FROM session_tags WHERE session_id = ?1")?
+            .execute(params![session.id])?;
+        for name in normalize_tags(&session.tags) {
+            tx.prepare_cached("INSERT OR IGNORE INTO tags (name) VALUES (?1)")?
+                .execute(params![name])?;
+            tx.prepare_cached(
+                "INSERT OR IGNORE INTO session_tags (session_id, tag_id)
+                 SELECT ?1, id FROM tags WHERE name = ?2",
+            )?
+            .execute(params![session.id, name])?;
+        }
+
+        tx.commit().or_error(
+            TimelyError::Storage,
+            "Failed to save session breaks and tags",
+        )?;
 
         Ok(())
     }
 
-    pub fn save_session(&self, session: &Session) -> Result<()> {
+    pub fn get_session(&self, id: &str) -> Result<Option<Session>> {
         let conn = self.get_connection()?;
 
-        let pauses_json = serde_json::to_string(&session.pauses)?;
-        let resumes_json = serde_json::to_string(&session.resumes)?;
-        let end_str = session.end.map(|dt| dt.to_rfc3339());
+        let mut stmt = conn.prepare_cached(&format!(
+            "SELECT {} FROM sessions WHERE id = ?1",
+            SESSION_COLUMNS
+        ))?;
 
-        conn.execute(
-            "INSERT OR REPLACE INTO sessions (id, start, pauses, resumes, end, total_seconds)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
-            params![
-                session.id,
-                session.start.to_rfc3339(),
-                pauses_json,
-                resumes_json,
-                end_str,
-                session.total_seconds,
-            ],
-        )
-        .context("Failed to save session")?;
+        let mut session = stmt
+            .query_row(params![id], session_from_row)
+            .optional()
+            .map_err(|e| unreadable("sessions", id, e))?;
+        if let Some(session) = session.as_mut() {
+            load_details(&conn, session).map_err(|e| unreadable("sessions", id, e))?;
+        }
+
+        Ok(session)
+    }
+
+    /// Record that the running session was seen alive at `at` with
+    /// `total_seconds` worked so far
+    pub fn record_heartbeat(
+        &self,
+        session_id: &str,
+        at: DateTime<Utc>,
+        total_seconds: i64,
+    ) -> Result<()> {
+        let conn = self.get_connection()?;
+
+        conn.prepare_cached(
+            "UPDATE sessions SET last_seen_at = ?1, total_seconds = ?2
+             WHERE id = ?3 AND end IS NULL",
+        )?
+        .execute(params![at.to_rfc3339(), total_seconds, session_id])
+        .or_error(TimelyError::Storage, "Failed to record heartbeat")?;
 
         Ok(())
     }
 
-    pub fn get_session(&self, id: &str) -> Result<Option<Session>> {
+    /// Every session still running, across all timers, newest first
+    pub fn get_active_sessions(&self) -> Result<Rows<Session>> {
         let conn = self.get_connection()?;
 
-        let mut stmt = conn.prepare(
-            "SELECT id, start, pauses, resumes, end, total_seconds FROM sessions WHERE id = ?1",
+        let mut stmt = conn.prepare_cached(&format!(
+            "SELECT {} FROM sessions WHERE end IS NULL ORDER BY start DESC",
+            SESSION_COLUMNS
+        ))?;
+
+        let mut skipped = Vec::new();
+        let sessions = readable_rows(
+            stmt.query_map([], with_id(session_from_row))?,
+            "sessions",
+            &mut skipped,
         )?;
+        let rows = with_details(&conn, sessions, &mut skipped)?;
 
-        let session = stmt
-            .query_row(params![id], |row| {
-                let pauses_json: String = row.get(2)?;
-                let resumes_json: String = row.get(3)?;
-                let end_str: Option<String> = row.get(4)?;
-
-                Ok(Session {
-                    id: row.get(0)?,
-                    start: DateTime::parse_from_rfc3339(&row.get::<_, String>(1)?)
-                        .unwrap()
-                        .with_timezone(&Utc),
-                    pauses: serde_json::from_str(&pauses_json).unwrap_or_default(),
-                    resumes: serde_json::from_str(&resumes_json).unwrap_or_default(),
-                    end: end_str
-                        .and_then(|s| DateTime::parse_from_rfc3339(&s).ok())
-                        .map(|dt| dt.with_timezone(&Utc)),
-                    total_seconds: row.get(5)?,
-                })
-            })
-            .optional()?;
+        Ok(Rows { rows, skipped })
+    }
 
-        Ok(session)
+    /// Get all sessions, newest first. When `tags` is non-empty only sessions
+    /// carrying every one of the given tags are returned.
+    pub fn get_all_sessions(&self, tags: &[String]) -> Result<Rows<Session>> {
+        let query = SessionQuery {
+            tags: tags.to_vec(),
+            ..SessionQuery::default()
+        };
+        let page = self.query_sessions(&query)?;
+        Ok(Rows {
+            rows: page.sessions,
+            skipped: page.skipped,
+        })
     }
 
-    pub fn get_active_session(&self) -> Result<Option<Session>> {
+    /// Get one page of sessions matching `query`, along with the total number of matches
+    pub fn query_sessions(&self, query: &SessionQuery) -> Result<SessionPage> {
         let conn = self.get_connection()?;
 
-        let mut stmt = conn.prepare(
-            "SELECT id, start, pauses, resumes, end, total_seconds 
+        let mut conditions: Vec<String> = Vec::new();
+        let mut values: Vec<Value> = Vec::new();
+
+        if let Some(from) = query.from {
+            conditions.push("start >= ?".to_string());
+            values.push(Value::Text(from.to_rfc3339()));
+        }
+        if let Some(to) = query.to {
+            conditions.push("start < ?".to_string());
+            values.push(Value::Text(to.to_rfc3339()));
+        }
+        if let Some(min) = query.min_duration_seconds {
+            conditions.push("total_seconds >= ?".to_string());
+            values.push(Value::Integer(min));
+        }
+        if let Some(max) = query.max_duration_seconds {
+            conditions.push("total_seconds <= ?".to_string());
+            values.push(Value::Integer(max));
+        }
+        match query.status {
+            Some(SessionStatus::Open) => conditions.push("end IS NULL".to_string()),
+            Some(SessionStatus::Closed) => conditions.push("end IS NOT NULL".to_string()),
+            None => {}
+        }
+        if let Some(project_id) = &query.project_id {
+            conditions.push("project_id = ?".to_string());
+            values.push(Value::Text(project_id.clone()));
+        }
+
+        let tags = normalize_tags(&query.tags);
+        if !tags.is_empty() {
+            conditions.push(format!(
+                "id IN (
+                    SELECT st.session_id FROM session_tags st
+                    JOIN tags t ON t.id = st.tag_id
+                    WHERE t.name IN ({})
+                    GROUP BY st.session_id
+                    HAVING COUNT(DISTINCT t.id) = {}
+                 )",
+                vec!["?"; tags.len()].join(", "),
+                tags.len()
+            ));
+            values.extend(tags.into_iter().map(Value::Text));
+        }
+
+        let where_clause = if conditions.is_empty() {
+            String::new()
+        } else {
+            format!("WHERE {}", conditions.join(" AND "))
+        };
+
+        let total_count: i64 = conn
+            .prepare_cached(&format!("SELECT COUNT(*) FROM sessions {}", where_clause))?
+            .query_row(params_from_iter(values.iter()), |row| row.get(0))?;
+
+        let order_by = match query.sort {
+            SessionSort::StartDesc => "start DESC",
+            SessionSort::StartAsc => "start ASC",
+            SessionSort::DurationDesc => "total_seconds DESC, start DESC",
+            SessionSort::DurationAsc => "total_seconds ASC, start DESC",
+        };
+
+        let offset = query.offset.unwrap_or(0);
+        // SQLite treats a negative LIMIT as no limit
+        let limit = query.limit.map(i64::from).unwrap_or(-1);
+        values.push(Value::Integer(limit));
+        values.push(Value::Integer(i64::from(offset)));
+
+        let mut stmt = conn.prepare_cached(&format!(
+            "SELECT {} 
              FROM sessions 
-             WHERE end IS NULL 
-             ORDER BY start DESC 
-             LIMIT 1",
+             {}
+             ORDER BY {}
+             LIMIT ? OFFSET ?",
+            SESSION_COLUMNS, where_clause, order_by
+        ))?;
+
+        let mut skipped = Vec::new();
+        let sessions = readable_rows(
+            stmt.query_map(params_from_iter(values.iter()), with_id(session_from_row))?,
+            "sessions",
+            &mut skipped,
         )?;
+        let sessions = with_details(&conn, sessions, &mut skipped)?;
 
-        let session = stmt
-            .query_row([], |row| {
-                let pauses_json: String = row.get(2)?;
-                let resumes_json: String = row.get(3)?;
-
-                Ok(Session {
-                    id: row.get(0)?,
-                    start: DateTime::parse_from_rfc3339(&row.get::<_, String>(1)?)
-                        .unwrap()
-                        .with_timezone(&Utc),
-                    pauses: serde_json::from_str(&pauses_json).unwrap_or_default(),
-                    resumes: serde_json::from_str(&resumes_json).unwrap_or_default(),
-                    end: None,
-                    total_seconds: row.get(5)?,
-                })
-            })
-            .optional()?;
+        // Skipped rows still take up their place in the paging
+        let next_offset = offset + (sessions.len() + skipped.len()) as u32;
+        let next_offset = (i64::from(next_offset) < total_count).then_some(next_offset);
+
+        Ok(SessionPage {
+            sessions,
+            total_count: total_count as usize,
+            next_offset,
+            skipped,
+        })
+    }
 
+    /// Get every tag name in use, for autocompletion
+    pub fn get_all_tags(&self) -> Result<Vec<String>> {
+        let conn = self.get_connection()?;
+
+        let mut stmt = conn.prepare_cached(
+            "SELECT name FROM tags
+             WHERE id IN (SELECT tag_id FROM session_tags)
+             ORDER BY name COLLATE NOCASE",
+        )?;
+
+        let tags = stmt
+            .query_map([], |row| row.get(0))?
+            .collect::<Result<Vec<String>, _>>()?;
+
+        Ok(tags)
+    }
+
+    /// Create a session from user-entered times, e.g. for time that wasn't tracked live
+    pub fn create_manual_session(&self, mut session: Session) -> Result<Session> {
+        self.validate_session(&mut session)?;
+        self.save_session(&session)?;
         Ok(session)
     }
 
-    pub fn get_all_sessions(&self) -> Result<Vec<Session>> {
+    /// Replace an ended session with a corrected copy
+    pub fn update_session(&self, mut session: Session) -> Result<Session> {
+        let existing = self
+            .get_session(&session.id)?
+            .or_error(TimelyError::NotFound, "Session not found")?;
+        if existing.is_active() {
+            fail!(Conflict, "Cannot edit a running session; end it first");
+        }
+
+        self.validate_session(&mut session)?;
+        self.save_session(&session)?;
+        Ok(session)
+    }
+
+    /// Delete an ended session. Works on rows that can't be read too, so a
+    /// corrupt session can always be removed.
+    pub fn delete_session(&self, id: &str) -> Result<()> {
+        let mut conn = self.get_connection()?;
+
+        let running: bool = conn
+            .query_row(
+                "SELECT end IS NULL FROM sessions WHERE id = ?1",
+                params![id],
+                |row| row.get(0),
+            )
+            .optional()?
+            .or_error(TimelyError::NotFound, "Session not found")?;
+        if running {
+            fail!(Conflict, "Cannot delete a running session; end it first");
+        }
+
+        let tx = conn.transaction()?;
+        tx.execute("DELETE FROM session_breaks WHERE session_id = ?1", params![id])?;
+        tx.execute("DELETE FROM session_tags WHERE session_id = ?1", params![id])?;
+        tx.execute("DELETE FROM sessions WHERE id = ?1", params![id])?;
+        tx.commit()
+            .or_error(TimelyError::Storage, "Failed to delete session")?;
+
+        Ok(())
+    }
+
+    /// Validate a user-supplied session against itself and the rest of the
+    /// store, and recompute its total from the timestamps.
+    fn validate_session(&self, session: &mut Session) -> Result<()> {
+        session.breaks.sort_by_key(|b| b.started_at);
+        for b in &mut session.breaks {
+            if b.id.is_empty() {
+                b.id = uuid::Uuid::new_v4().to_string();
+            }
+            b.session_id = session.id.clone();
+        }
+
+        session.validate()?;
+
+        if let Some(project_id) = session.project_id.as_deref() {
+            if self.get_project(project_id)?.is_none() {
+                fail!(NotFound, "Project not found");
+            }
+        }
+
+        if let Some(other) = self.find_overlapping_session(session)? {
+            fail!(
+                Conflict,
+                "Session overlaps with another session starting at {}",
+                other.start.format("%Y-%m-%d %H:%M")
+            );
+        }
+
+        session.total_seconds = session.calculate_total_seconds();
+        Ok(())
+    }
+
+    /// A session on the same timer overlapping `session`. Different timers
+    /// are meant to run concurrently.
+    fn find_overlapping_session(&self, session: &Session) -> Result<Option<Session>> {
         let conn = self.get_connection()?;
+        let end = session.end.unwrap_or_else(Utc::now);
 
-        let mut stmt = conn.prepare(
-            "SELECT id, start, pauses, resumes, end, total_seconds 
+        // Running sessions overlap anything after their start
+        let mut stmt = conn.prepare_cached(&format!(
+            "SELECT {} 
              FROM sessions 
-             ORDER BY start DESC",
+             WHERE id != ?1 AND start < ?2 AND (end IS NULL OR end > ?3) AND timer_name = ?4
+             ORDER BY start ASC
+             LIMIT 1",
+            SESSION_COLUMNS
+        ))?;
+
+        let other = stmt
+            .query_row(
+                params![
+                    session.id,
+                    end.to_rfc3339(),
+                    session.start.to_rfc3339(),
+                    session.timer_name
+                ],
+                with_id(session_from_row),
+            )
+            .optional()?;
+
+        // An overlap that can't be read still blocks the save, naming the row
+        other
+            .map(|(id, other)| other.map_err(|e| unreadable("sessions", &id, e)))
+            .transpose()
+    }
+
+    fn get_setting<T: DeserializeOwned>(&self, key: &str) -> Result<Option<T>> {
+        let conn = self.get_connection()?;
+
+        let value: Option<String> = conn
+            .prepare_cached("SELECT value FROM settings WHERE key = ?1")?
+            .query_row(params![key], |row| row.get(0))
+            .optional()?;
+
+        value
+            .map(|v| serde_json::from_str(&v))
+            .transpose()
+            .map_err(|e| {
+                TimelyError::Storage(format!("Invalid value for setting '{}': {}", key, e))
+            })
+    }
+
+    fn save_setting<T: Serialize>(&self, key: &str, value: &T) -> Result<()> {
+        let conn = self.get_connection()?;
+
+        conn.prepare_cached("INSERT OR REPLACE INTO settings (key, value) VALUES (?1, ?2)")?
+            .execute(params![key, serde_json::to_string(value)?])
+            .map_err(|e| {
+                TimelyError::Storage(format!("Failed to save setting '{}': {}", key, e))
+            })?;
+
+        Ok(())
+    }
+
+    pub fn get_work_rules(&self) -> Result<WorkRules> {
+        Ok(self.get_setting(WORK_RULES_KEY)?.unwrap_or_default())
+    }
+
+    pub fn save_work_rules(&self, rules: &WorkRules) -> Result<()> {
+        rules.validate()?;
+        self.save_setting(WORK_RULES_KEY, rules)
+    }
+
+    /// The time zone days, weeks and months are bucketed in. Defaults to the
+    /// system time zone until the user picks one.
+    pub fn get_timezone(&self) -> Result<Tz> {
+        match self.get_setting::<String>(TIMEZONE_KEY)? {
+            Some(name) => local_time::parse_timezone(&name),
+            None => Ok(local_time::system_timezone()),
+        }
+    }
+
+    pub fn save_timezone(&self, name: &str) -> Result<Tz> {
+        let tz = local_time::parse_timezone(name)?;
+        self.save_setting(TIMEZONE_KEY, &tz.name())?;
+        Ok(tz)
+    }
+
+    pub fn get_idle_settings(&self) -> Result<IdleSettings> {
+        Ok(self.get_setting(IDLE_SETTINGS_KEY)?.unwrap_or_default())
+    }
+
+    pub fn save_idle_settings(&self, settings: &IdleSettings) -> Result<()> {
+        settings.validate()?;
+        self.save_setting(IDLE_SETTINGS_KEY, settings)
+    }
+
+    pub fn get_pomodoro_settings(&self) -> Result<PomodoroSettings> {
+        Ok(self.get_setting(POMODORO_SETTINGS_KEY)?.unwrap_or_default())
+    }
+
+    pub fn save_pomodoro_settings(&self, settings: &PomodoroSettings) -> Result<()> {
+        settings.validate()?;
+        self.save_setting(POMODORO_SETTINGS_KEY, settings)
+    }
+
+    pub fn get_hotkey_settings(&self) -> Result<HotkeySettings> {
+        Ok(self.get_setting(HOTKEY_SETTINGS_KEY)?.unwrap_or_default())
+    }
+
+    pub fn save_hotkey_settings(&self, settings: &HotkeySettings) -> Result<()> {
+        settings.validate()?;
+        self.save_setting(HOTKEY_SETTINGS_KEY, settings)
+    }
+
+    /// Name of the timer last made primary, used to pick it again after a restart
+    pub fn get_primary_timer(&self) -> Result<Option<String>> {
+        self.get_setting(PRIMARY_TIMER_KEY)
+    }
+
+    pub fn save_primary_timer(&self, name: &str) -> Result<()> {
+        self.save_setting(PRIMARY_TIMER_KEY, &name)
+    }
+
+    pub fn save_client(&self, client: &Client) -> Result<()> {
+        let conn = self.get_connection()?;
+
+        conn.execute(
+            "INSERT OR REPLACE INTO clients (id, name, created_at) VALUES (?1, ?2, ?3)",
+            params![client.id, client.name, client.created_at.to_rfc3339()],
+        )
+        .or_error(TimelyError::Storage, "Failed to save client")?;
+
+        Ok(())
+    }
+
+    pub fn get_client(&self, id: &str) -> Result<Option<Client>> {
+        let conn = self.get_connection()?;
+
+        let client = conn
+            .query_row(
+                "SELECT id, name, created_at FROM clients WHERE id = ?1",
+                params![id],
+                client_from_row,
+            )
+            .optional()
+            .map_err(|e| unreadable("clients", id, e))?;
+
+        Ok(client)
+    }
+
+    pub fn get_all_clients(&self) -> Result<Rows<Client>> {
+        let conn = self.get_connection()?;
+
+        let mut stmt =
+            conn.prepare_cached("SELECT id, name, created_at FROM clients ORDER BY name COLLATE NOCASE")?;
+
+        let mut skipped = Vec::new();
+        let rows = readable_rows(
+            stmt.query_map([], with_id(client_from_row))?,
+            "clients",
+            &mut skipped,
         )?;
 
-        let sessions = stmt
-            .query_map([], |row| {
-                let pauses_json: String = row.get(2)?;
-                let resumes_json: String = row.get(3)?;
-                let end_str: Option<String> = row.get(4)?;
-
-                Ok(Session {
-                    id: row.get(0)?,
-                    start: DateTime::parse_from_rfc3339(&row.get::<_, String>(1)?)
-                        .unwrap()
-                        .with_timezone(&Utc),
-                    pauses: serde_json::from_str(&pauses_json).unwrap_or_default(),
-                    resumes: serde_json::from_str(&resumes_json).unwrap_or_default(),
-                    end: end_str
-                        .and_then(|s| DateTime::parse_from_rfc3339(&s).ok())
-                        .map(|dt| dt.with_timezone(&Utc)),
-                    total_seconds: row.get(5)?,
-                })
-            })?
-            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Rows { rows, skipped })
+    }
+
+    /// Delete a client, detaching its projects rather than deleting them
+    pub fn delete_client(&self, id: &str) -> Result<()> {
+        let mut conn = self.get_connection()?;
+        let tx = conn.transaction()?;
+
+        tx.prepare_cached(
+            "UPDATE projects SET client_id = NULL WHERE client_id = ?1",
+        )?
+        .execute(params![id])?;
+        let deleted = tx.execute("DELETE FROM clients WHERE id = ?1", params![id])?;
+        if deleted == 0 {
+            fail!(NotFound, "Client not found");
+        }
+
+        tx.commit()
+            .or_error(TimelyError::Storage, "Failed to delete client")?;
+        Ok(())
+    }
+
+    pub fn save_project(&self, project: &Project) -> Result<()> {
+        if let Some(client_id) = project.client_id.as_deref() {
+            if self.get_client(client_id)?.is_none() {
+                fail!(Validation, "Client not found");
+            }
+        }
+
+        let conn = self.get_connection()?;
+
+        conn.execute(
+            "INSERT OR REPLACE INTO projects (id, name, client_id, created_at)
+             VALUES (?1, ?2, ?3, ?4)",
+            params![
+                project.id,
+                project.name,
+                project.client_id,
+                project.created_at.to_rfc3339(),
+            ],
+        )
+        .or_error(TimelyError::Storage, "Failed to save project")?;
+
+        Ok(())
+    }
+
+    pub fn get_project(&self, id: &str) -> Result<Option<Project>> {
+        let conn = self.get_connection()?;
+
+        let project = conn
+            .query_row(
+                "SELECT id, name, client_id, created_at FROM projects WHERE id = ?1",
+                params![id],
+                project_from_row,
+            )
+            .optional()
+            .map_err(|e| unreadable("projects", id, e))?;
+
+        Ok(project)
+    }
+
+    pub fn get_all_projects(&self) -> Result<Rows<Project>> {
+        let conn = self.get_connection()?;
+
+        let mut stmt = conn.prepare_cached(
+            "SELECT id, name, client_id, created_at FROM projects ORDER BY name COLLATE NOCASE",
+        )?;
+
+        let mut skipped = Vec::new();
+        let rows = readable_rows(
+            stmt.query_map([], with_id(project_from_row))?,
+            "projects",
+            &mut skipped,
+        )?;
+
+        Ok(Rows { rows, skipped })
+    }
+
+    /// Delete a project, leaving its sessions in place without a project
+    pub fn delete_project(&self, id: &str) -> Result<()> {
+        let mut conn = self.get_connection()?;
+        let tx = conn.transaction()?;
 
-        Ok(sessions)
+        tx.prepare_cached(
+            "UPDATE sessions SET project_id = NULL WHERE project_id = ?1",
+        )?
+        .execute(params![id])?;
+        let deleted = tx.execute("DELETE FROM projects WHERE id = ?1", params![id])?;
+        if deleted == 0 {
+            fail!(NotFound, "Project not found");
+        }
+
+        tx.commit()
+            .or_error(TimelyError::Storage, "Failed to delete project")?;
+        Ok(())
     }
 
     pub fn get_monthly_summary(&self, year: i32, month: u32) -> Result<MonthlySummary> {
-        use chrono::Datelike;
-        use crate::models::WeeklySummary;
-        
-        let sessions = self.get_sessions_for_month(year, month)?;
+        let summary = self.get_summary(
+            &SummaryRange::Month { year, month },
+            Granularity::Day,
+            OverlapMode::default(),
+        )?;
+
+        Ok(MonthlySummary {
+            year,
+            month,
+            total_seconds: summary.total_seconds,
+            break_seconds: summary.break_seconds,
+            regular_hours: summary.regular_hours,
+            overtime_hours: summary.overtime_hours,
+            weighted_hours: summary.weighted_hours,
+            session_count: summary.session_count,
+            longest_session_seconds: summary.longest_session_seconds,
+            daily_breakdown: summary
+                .breakdown
+                .into_iter()
+                .map(|day| DailySummary {
+                    date: day.start.format("%Y-%m-%d").to_string(),
+                    total_seconds: day.total_seconds,
+                    session_count: day.session_count,
+                    in_progress: day.in_progress,
+                })
+                .collect(),
+            weekly_breakdown: summary.weekly_breakdown,
+            project_breakdown: summary.project_breakdown,
+            skipped: summary.skipped,
+        })
+    }
+
+    /// Summarize the sessions worked within `range`, broken down into periods of
+    /// `granularity`. Days are local to the configured time zone. `overlap`
+    /// decides whether time on several timers at once counts once or per timer.
+    pub fn get_summary(
+        &self,
+        range: &SummaryRange,
+        granularity: Granularity,
+        overlap: OverlapMode,
+    ) -> Result<Summary> {
+        let rules = self.get_work_rules()?;
+        let tz = self.get_timezone()?;
+        let (first_day, last_day) = range.dates()?;
+        let after_last_day = last_day
+            .succ_opt()
+            .or_error(TimelyError::Validation, "Invalid date")?;
+        let break_seconds = self.get_break_seconds(
+            local_time::start_of_day(tz, first_day),
+            local_time::start_of_day(tz, after_last_day),
+        )?;
+
+        // Weeks are summarized in full, so look at every day of the weeks the range touches
+        let range_start = rules.week_start_for(first_day);
+        let range_end = rules.week_start_for(last_day) + chrono::Duration::days(7);
+        let Rows {
+            rows: sessions,
+            mut skipped,
+        } = self.get_sessions_overlapping(
+            local_time::start_of_day(tz, range_start),
+            local_time::start_of_day(tz, range_end),
+        )?;
+        let in_range = |day: &NaiveDate| (first_day..=last_day).contains(day);
+        let now = Utc::now();
 
         let mut total_seconds = 0i64;
+        let mut session_count = 0usize;
         let mut longest_session_seconds = 0i64;
-        let mut daily_map: std::collections::HashMap<String, (i64, usize)> =
-            std::collections::HashMap::new();
-        let mut weekly_map: std::collections::HashMap<String, (i64, usize)> =
-            std::collections::HashMap::new();
+        // Period start -> (period end, seconds, session count, has a running session)
+        let mut period_map: BTreeMap<NaiveDate, (NaiveDate, i64, usize, bool)> = BTreeMap::new();
+        // Week start date -> (seconds per day, session count)
+        let mut weekly_map: BTreeMap<NaiveDate, (BTreeMap<NaiveDate, i64>, usize)> =
+            BTreeMap::new();
+        let mut project_map: HashMap<Option<String>, (i64, usize)> = HashMap::new();
+        let mut timer_map: HashMap<String, (i64, usize)> = HashMap::new();
+        // Time already counted, when overlapping sessions count once
+        let mut covered = Vec::new();
 
         for session in &sessions {
-            total_seconds += session.total_seconds;
-            longest_session_seconds = longest_session_seconds.max(session.total_seconds);
+            // Apportion worked time to the days it fell on, so sessions crossing
+            // midnight or the range boundary only count their share here.
+            // Running sessions count up to now.
+            let in_progress = session.is_active();
+            let end = session.end.unwrap_or(now);
+            let mut days = match overlap {
+                OverlapMode::PerTimer => intervals::seconds_per_day(session, end, tz),
+                OverlapMode::Once => {
+                    let worked = intervals::worked_intervals(session, end);
+                    intervals::split_by_day(&intervals::take_uncovered(&worked, &mut covered), tz)
+                }
+            };
+            // A session with no worked time yet still counts on the day it started
+            if days.is_empty() {
+                days.insert(local_time::local_date(tz, session.start), 0);
+            }
+
+            let mut weeks_seen = HashSet::new();
+            for (day, seconds) in &days {
+                let week_start = rules.week_start_for(*day);
+                let week_entry = weekly_map.entry(week_start).or_default();
+                *week_entry.0.entry(*day).or_insert(0) += seconds;
+                if weeks_seen.insert(week_start) {
+                    week_entry.1 += 1;
+                }
+            }
+
+            let days_in_range: Vec<(NaiveDate, i64)> =
+                days.into_iter().filter(|(day, _)| in_range(day)).collect();
+            if days_in_range.is_empty() {
+                continue;
+            }
 
-            let date_key = session.start.format("%Y-%m-%d").to_string();
-            let entry = daily_map.entry(date_key).or_insert((0, 0));
-            entry.0 += session.total_seconds;
-            entry.1 += 1;
+            let seconds_in_range: i64 = days_in_range.iter().map(|(_, seconds)| seconds).sum();
+            total_seconds += seconds_in_range;
+            session_count += 1;
+            longest_session_seconds =
+                longest_session_seconds.max(session.calculate_total_seconds());
 
-            // Calculate week key (ISO week format)
-            let iso_week = session.start.iso_week();
-            let week_key = format!("{}-W{:02}", iso_week.year(), iso_week.week());
-            let week_entry = weekly_map.entry(week_key).or_insert((0, 0));
-            week_entry.0 += session.total_seconds;
-            week_entry.1 += 1;
+            let mut periods_seen = HashSet::new();
+            for (day, seconds) in days_in_range {
+                let (period_start, period_end) = granularity.period_for(day, rules.week_start);
+                let entry = period_map
+                    .entry(period_start)
+                    .or_insert((period_end, 0, 0, false));
+                entry.1 += seconds;
+                entry.3 |= in_progress;
+                if periods_seen.insert(period_start) {
+                    entry.2 += 1;
+                }
+            }
+
+            let project_entry = project_map
+                .entry(session.project_id.clone())
+                .or_insert((0, 0));
+            project_entry.0 += seconds_in_range;
+            project_entry.1 += 1;
+
+            let timer_entry = timer_map
+                .entry(session.timer_name.clone())
+                .or_insert((0, 0));
+            timer_entry.0 += seconds_in_range;
+            timer_entry.1 += 1;
         }
 
-        let mut daily_breakdown: Vec<DailySummary> = daily_map
+        let breakdown: Vec<PeriodSummary> = period_map
             .into_iter()
-            .map(|(date, (seconds, count))| DailySummary {
-                date,
+            .map(|(start, (end, seconds, count, in_progress))| PeriodSummary {
+                start,
+                end,
                 total_seconds: seconds,
                 session_count: count,
+                in_progress,
             })
             .collect();
 
-        daily_breakdown.sort_by(|a, b| a.date.cmp(&b.date));
-
-        // Calculate weekly breakdown with overtime (40 hours = 144000 seconds)
-        let mut weekly_breakdown: Vec<WeeklySummary> = weekly_map
+        // Weekly breakdown with overtime according to the configured work rules
+        let weekly_breakdown: Vec<WeeklySummary> = weekly_map
             .into_iter()
-            .map(|(week_key, (seconds, count))| {
-                let total_hours = seconds as f64 / 3600.0;
-                let regular_hours = total_hours.min(40.0);
-                let overtime_hours = (total_hours - 40.0).max(0.0);
-                
-                // Parse week to get start and end dates
-                let parts: Vec<&str> = week_key.split("-W").collect();
-                let year = parts[0].parse::<i32>().unwrap_or(year);
-                let week = parts.get(1).and_then(|w| w.parse::<u32>().ok()).unwrap_or(1);
-                
+            .filter(|(_, (days, _))| days.keys().any(in_range))
+            .map(|(week_start, (days, count))| {
+                let daily_hours: Vec<f64> =
+                    days.values().map(|seconds| *seconds as f64 / 3600.0).collect();
+                let split = rules.split_week(&daily_hours);
+                let in_range_seconds: i64 = days
+                    .iter()
+                    .filter(|(day, _)| in_range(day))
+                    .map(|(_, seconds)| seconds)
+                    .sum();
+
                 WeeklySummary {
-                    week_start: format!("{} Week {}", year, week),
-                    week_end: format!("{} Week {}", year, week),
-                    regular_hours,
-                    overtime_hours,
-                    total_hours,
+                    week_start,
+                    week_end: week_start + chrono::Duration::days(6),
+                    regular_hours: split.regular_hours,
+                    overtime_hours: split.overtime_hours,
+                    weighted_hours: split.weighted_hours,
+                    total_hours: daily_hours.iter().sum(),
+                    in_range_hours: in_range_seconds as f64 / 3600.0,
                     session_count: count,
+                    days: week_start
+                        .iter_days()
+                        .take(7)
+                        .map(|date| WeekDaySummary {
+                            date,
+                            total_seconds: days.get(&date).copied().unwrap_or(0),
+                            in_range: in_range(&date),
+                        })
+                        .collect(),
                 }
             })
             .collect();
 
-        weekly_breakdown.sort_by(|a, b| a.week_start.cmp(&b.week_start));
+        let projects = self.get_all_projects()?;
+        let clients = self.get_all_clients()?;
+        skipped.extend(projects.skipped);
+        skipped.extend(clients.skipped);
+        let projects: HashMap<String, Project> = projects
+            .rows
+            .into_iter()
+            .map(|p| (p.id.clone(), p))
+            .collect();
+        let clients: HashMap<String, Client> = clients
+            .rows
+            .into_iter()
+            .map(|c| (c.id.clone(), c))
+            .collect();
+
+        let mut project_breakdown: Vec<ProjectSummary> = project_map
+            .into_iter()
+            .map(|(project_id, (seconds, count))| {
+                let project = project_id.as_ref().and_then(|id| projects.get(id));
+                let client = project
+                    .and_then(|p| p.client_id.as_ref())
+                    .and_then(|id| clients.get(id));
+
+                ProjectSummary {
+                    project_id,
+                    project_name: project.map(|p| p.name.clone()),
+                    client_id: client.map(|c| c.id.clone()),
+                    client_name: client.map(|c| c.name.clone()),
+                    total_seconds: seconds,
+                    session_count: count,
+                }
+            })
+            .collect();
+
+        project_breakdown.sort_by_key(|p| std::cmp::Reverse(p.total_seconds));
+
+        let mut timer_breakdown: Vec<TimerSummary> = timer_map
+            .into_iter()
+            .map(|(timer_name, (seconds, count))| TimerSummary {
+                timer_name,
+                total_seconds: seconds,
+                session_count: count,
+            })
+            .collect();
+        timer_breakdown.sort_by_key(|t| std::cmp::Reverse(t.total_seconds));
 
-        // Calculate total regular and overtime hours for the month
+        // Weeks straddling the range boundary contribute overtime in proportion
+        // to the share of their hours worked inside the range
+        let in_range_share = |w: &WeeklySummary| {
+            if w.total_hours > 0.0 {
+                w.in_range_hours / w.total_hours
+            } else {
+                0.0
+            }
+        };
         let total_hours = total_seconds as f64 / 3600.0;
-        let total_overtime_hours: f64 = weekly_breakdown.iter().map(|w| w.overtime_hours).sum();
+        let total_overtime_hours: f64 = weekly_breakdown
+            .iter()
+            .map(|w| w.overtime_hours * in_range_share(w))
+            .sum();
         let total_regular_hours = total_hours - total_overtime_hours;
+        let total_weighted_hours: f64 = weekly_breakdown
+            .iter()
+            .map(|w| w.weighted_hours * in_range_share(w))
+            .sum();
 
-        Ok(MonthlySummary {
-            year,
-            month,
+        Ok(Summary {
+            start: first_day,
+            end: last_day,
+            granularity,
             total_seconds,
+            break_seconds,
             regular_hours: total_regular_hours,
             overtime_hours: total_overtime_hours,
-            session_count: sessions.len(),
+            weighted_hours: total_weighted_hours,
+            session_count,
             longest_session_seconds,
-            daily_breakdown,
+            breakdown,
             weekly_breakdown,
+            project_breakdown,
+            overlap,
+            timer_breakdown,
+            skipped,
         })
     }
 
-    fn get_sessions_for_month(&self, year: i32, month: u32) -> Result<Vec<Session>> {
-        let start_date = NaiveDate::from_ymd_opt(year, month, 1)
-            .context("Invalid date")?
-            .and_hms_opt(0, 0, 0)
-            .unwrap()
-            .and_utc();
+    /// Get every break of sessions starting within `[from, to)`, oldest first
+    pub fn get_breaks(&self, from: DateTime<Utc>, to: DateTime<Utc>) -> Result<Rows<Break>> {
+        let conn = self.get_connection()?;
 
-        let end_date = if month == 12 {
-            NaiveDate::from_ymd_opt(year + 1, 1, 1)
-        } else {
-            NaiveDate::from_ymd_opt(year, month + 1, 1)
-        }
-        .context("Invalid date")?
-        .and_hms_opt(0, 0, 0)
-        .unwrap()
-        .and_utc();
+        let mut stmt = conn.prepare_cached(&format!(
+            "SELECT {} FROM session_breaks
+             WHERE session_id IN (SELECT id FROM sessions WHERE start >= ?1 AND start < ?2)
+             ORDER BY started_at ASC",
+            BREAK_COLUMNS
+        ))?;
 
+        let mut skipped = Vec::new();
+        let rows = readable_rows(
+            stmt.query_map(
+                params![from.to_rfc3339(), to.to_rfc3339()],
+                with_id(break_from_row),
+            )?,
+            "session_breaks",
+            &mut skipped,
+        )?;
+
+        Ok(Rows { rows, skipped })
+    }
+
+    /// Sum the break time of sessions starting within `[from, to)`. An open
+    /// break counts up to its session's end, or up to now if still running.
+    fn get_break_seconds(&self, from: DateTime<Utc>, to: DateTime<Utc>) -> Result<i64> {
         let conn = self.get_connection()?;
-        let mut stmt = conn.prepare(
-            "SELECT id, start, pauses, resumes, end, total_seconds 
+
+        let seconds: Option<i64> = conn.query_row(
+            "SELECT CAST(ROUND(SUM(
+                 julianday(COALESCE(b.ended_at, s.end, ?3)) - julianday(b.started_at)
+             ) * 86400) AS INTEGER)
+             FROM session_breaks b
+             JOIN sessions s ON s.id = b.session_id
+             WHERE s.start >= ?1 AND s.start < ?2",
+            params![from.to_rfc3339(), to.to_rfc3339(), Utc::now().to_rfc3339()],
+            |row| row.get(0),
+        )?;
+
+        Ok(seconds.unwrap_or(0))
+    }
+
+    /// Get every session that was running at some point within `[from, to)`,
+    /// including one still running, oldest first
+    pub fn get_sessions_overlapping(
+        &self,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Rows<Session>> {
+        let conn = self.get_connection()?;
+
+        let mut stmt = conn.prepare_cached(&format!(
+            "SELECT {} 
              FROM sessions 
-             WHERE start >= ?1 AND start < ?2
+             WHERE start < ?2 AND (end IS NULL OR end > ?1)
              ORDER BY start ASC",
+            SESSION_COLUMNS
+        ))?;
+
+        let mut skipped = Vec::new();
+        let sessions = readable_rows(
+            stmt.query_map(
+                params![from.to_rfc3339(), to.to_rfc3339()],
+                with_id(session_from_row),
+            )?,
+            "sessions",
+            &mut skipped,
         )?;
+        let rows = with_details(&conn, sessions, &mut skipped)?;
+
+        Ok(Rows { rows, skipped })
+    }
+
+    /// Get the sessions starting within the given month in the user's time zone
+    pub fn get_sessions_for_month(&self, year: i32, month: u32) -> Result<Rows<Session>> {
+        let (start_date, end_date) = local_time::month_bounds(self.get_timezone()?, year, month)?;
 
-        let sessions = stmt
-            .query_map(
-                params![start_date.to_rfc3339(), end_date.to_rfc3339()],
-                |row| {
-                    let pauses_json: String = row.get(2)?;
-                    let resumes_json: String = row.get(3)?;
-                    let end_str: Option<String> = row.get(4)?;
-
-                    Ok(Session {
-                        id: row.get(0)?,
-                        start: DateTime::parse_from_rfc3339(&row.get::<_, String>(1)?)
-                            .unwrap()
-                            .with_timezone(&Utc),
-                        pauses: serde_json::from_str(&pauses_json).unwrap_or_default(),
-                        resumes: serde_json::from_str(&resumes_json).unwrap_or_default(),
-                        end: end_str
-                            .and_then(|s| DateTime::parse_from_rfc3339(&s).ok())
-                            .map(|dt| dt.with_timezone(&Utc)),
-                        total_seconds: row.get(5)?,
-                    })
-                },
+        let query = SessionQuery {
+            from: Some(start_date),
+            to: Some(end_date),
+            sort: SessionSort::StartAsc,
+            ..SessionQuery::default()
+        };
+        let page = self.query_sessions(&query)?;
+        Ok(Rows {
+            rows: page.sessions,
+            skipped: page.skipped,
+        })
+    }
+
+    /// Scan every stored row for timestamps that can't be read, breaks that
+    /// don't pair up, negative durations and stale totals. With `fix`, repair
+    /// what can be repaired without guessing; timestamps that can't be read
+    /// at all and sessions ending before they start are only reported.
+    /// Running sessions belong to the timer, so only their timestamps are
+    /// checked.
+    pub fn check_integrity(&self, fix: bool) -> Result<IntegrityReport> {
+        let mut conn = self.get_connection()?;
+        let tx = conn.transaction()?;
+        let mut check = IntegrityCheck {
+            tx: &tx,
+            fix,
+            issues: Vec::new(),
+        };
+
+        for table in ["clients", "projects"] {
+            let rows = tx
+                .prepare(&format!("SELECT id, created_at FROM {}", table))?
+                .query_map([], |row| {
+                    Ok((row.get::<_, String>(0)?, raw_text_at(row, 1)?))
+                })?
+                .collect::<rusqlite::Result<Vec<_>>>()?;
+            for (id, created_at) in rows {
+                check.timestamp(
+                    table,
+                    "created_at",
+                    &id,
+                    &created_at.unwrap_or_default(),
+                    false,
+                )?;
+            }
+        }
+
+        let sessions = tx
+            .prepare(
+                "SELECT id, start, end, total_seconds, last_seen_at FROM sessions ORDER BY start",
             )?
-            .collect::<Result<Vec<_>, _>>()?;
+            .query_map([], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    raw_text_at(row, 1)?.unwrap_or_default(),
+                    raw_text_at(row, 2)?,
+                    row.get::<_, i64>(3)?,
+                    raw_text_at(row, 4)?,
+                ))
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        let mut breaks_checked = 0;
+        for (id, start, end, total_seconds, last_seen_at) in &sessions {
+            let start = check.timestamp("sessions", "start", id, start, false)?;
+            let end = match end {
+                Some(end) => check
+                    .timestamp("sessions", "end", id, end, false)?
+                    .map(Some),
+                None => Some(None),
+            };
+            if let Some(last_seen_at) = last_seen_at {
+                check.timestamp("sessions", "last_seen_at", id, last_seen_at, true)?;
+            }
+
+            let raw_breaks = tx
+                .prepare_cached(
+                    "SELECT id, started_at, ended_at, reason FROM session_breaks
+                     WHERE session_id = ?1",
+                )?
+                .query_map(params![id], |row| {
+                    Ok((
+                        row.get::<_, String>(0)?,
+                        raw_text_at(row, 1)?.unwrap_or_default(),
+                        raw_text_at(row, 2)?,
+                        row.get::<_, Option<String>>(3)?,
+                    ))
+                })?
+                .collect::<rusqlite::Result<Vec<_>>>()?;
+            let break_count = raw_breaks.len();
+            breaks_checked += break_count;
+
+            let mut breaks = Vec::with_capacity(break_count);
+            for (break_id, started_at, ended_at, reason) in raw_breaks {
+                let started_at = check.timestamp(
+                    "session_breaks",
+                    "started_at",
+                    &break_id,
+                    &started_at,
+                    false,
+                )?;
+                let ended_at = match ended_at {
+                    Some(ended_at) => check
+                        .timestamp("session_breaks", "ended_at", &break_id, &ended_at, false)?
+                        .map(Some),
+                    None => Some(None),
+                };
+                if let (Some(started_at), Some(ended_at)) = (started_at, ended_at) {
+                    breaks.push(Break {
+                        id: break_id,
+                        session_id: id.clone(),
+                        started_at,
+                        ended_at,
+                        reason,
+                    });
+                }
+            }
+
+            // The rest needs every timestamp of an ended session
+            let (Some(start), Some(Some(end))) = (start, end) else {
+                continue;
+            };
+            if breaks.len() < break_count {
+                continue;
+            }
+            if end < start {
+                check.issues.push(IntegrityIssue::new(
+                    IntegrityIssueKind::NegativeDuration,
+                    "sessions",
+                    id,
+                    "Session ends before it starts",
+                ));
+                continue;
+            }
+
+            let mut session = Session {
+                id: id.clone(),
+                start,
+                breaks,
+                end: Some(end),
+                total_seconds: *total_seconds,
+                ..Session::new(None)
+            };
+            let break_issues = session.repair_breaks();
+            if !break_issues.is_empty() {
+                if fix {
+                    replace_breaks(&tx, &session)?;
+                }
+                check
+                    .issues
+                    .extend(break_issues.into_iter().map(|issue| IntegrityIssue {
+                        fixed: fix,
+                        ..issue
+                    }));
+            }
+
+            let expected = session.calculate_total_seconds();
+            if *total_seconds != expected {
+                let kind = if *total_seconds < 0 {
+                    IntegrityIssueKind::NegativeDuration
+                } else {
+                    IntegrityIssueKind::StaleTotal
+                };
+                let message = format!(
+                    "Total is {}s but the session's times add up to {}s",
+                    total_seconds, expected
+                );
+                check.repair(IntegrityIssue::new(kind, "sessions", id, &message), |tx| {
+                    tx.execute(
+                        "UPDATE sessions SET total_seconds = ?1 WHERE id = ?2",
+                        params![expected, id],
+                    )
+                })?;
+            }
+        }
+
+        let legacy_errors = tx
+            .prepare(
+                "SELECT id, session_id, source_column, position, value, error
+                 FROM legacy_break_errors ORDER BY id",
+            )?
+            .query_map([], |row| {
+                Ok((
+                    row.get::<_, i64>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, String>(2)?,
+                    row.get::<_, Option<i64>>(3)?,
+                    row.get::<_, String>(4)?,
+                    row.get::<_, String>(5)?,
+                ))
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        for (id, session_id, column, position, value, error) in legacy_errors {
+            let entry = match position {
+                Some(position) => format!("{} entry {}", column, position),
+                None => column,
+            };
+            let message = format!(
+                "Session {}: {} {} couldn't be read ({})",
+                session_id, entry, value, error
+            );
+            check.issues.push(IntegrityIssue::new(
+                IntegrityIssueKind::UnreadableLegacyBreak,
+                "legacy_break_errors",
+                &id.to_string(),
+                &message,
+            ));
+        }
+
+        let report = IntegrityReport {
+            sessions_checked: sessions.len(),
+            breaks_checked,
+            issues: check.issues,
+        };
+        if fix {
+            tx.commit()
+                .or_error(TimelyError::Storage, "Failed to save integrity fixes")?;
+        }
+
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::TempDir;
+    use chrono::Duration as ChronoDuration;
+    use chrono::{Datelike, TimeZone};
+    use std::time::Instant;
+
+    fn at(hour: u32, minute: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2025, 3, 10, hour, minute, 0).unwrap()
+    }
+
+    /// A store summarizing in UTC, so test days are the same everywhere
+    fn utc_store(dir: &TempDir) -> SessionStore {
+        let store = SessionStore::new(dir.path().join("store.db")).unwrap();
+        store.save_timezone("UTC").unwrap();
+        store
+    }
+
+    fn day_summary(store: &SessionStore) -> Summary {
+        let range = SummaryRange::Day {
+            date: at(0, 0).date_naive(),
+        };
+        store
+            .get_summary(&range, Granularity::Day, OverlapMode::PerTimer)
+            .unwrap()
+    }
+
+    /// An ended session saved as-is, without the validation manual sessions get
+    fn saved(store: &SessionStore, start: u32, end: u32, breaks: &[(u32, Option<u32>)]) -> Session {
+        let mut session = Session::new(None);
+        session.start = at(start, 0);
+        session.end = Some(at(end, 0));
+        for &(started, ended) in breaks {
+            let mut b = Break::new(&session.id, at(started, 0));
+            b.ended_at = ended.map(|hour| at(hour, 0));
+            session.breaks.push(b);
+        }
+        session.total_seconds = session.calculate_total_seconds();
+        store.save_session(&session).unwrap();
+        session
+    }
+
+    fn corrupt(store: &SessionStore, sql: &str, id: &str) {
+        store
+            .get_connection()
+            .unwrap()
+            .execute(sql, params![id])
+            .unwrap();
+    }
+
+    #[test]
+    fn unreadable_rows_are_skipped_and_reported() {
+        let dir = TempDir::new();
+        let store = SessionStore::new(dir.path().join("store.db")).unwrap();
+        let good = saved(&store, 9, 10, &[]);
+        let bad = saved(&store, 11, 12, &[]);
+        let bad_break = saved(&store, 13, 15, &[(14, Some(14))]);
+        corrupt(
+            &store,
+            "UPDATE sessions SET start = 'last tuesday' WHERE id = ?1",
+            &bad.id,
+        );
+        corrupt(
+            &store,
+            "UPDATE session_breaks SET ended_at = 'soon' WHERE session_id = ?1",
+            &bad_break.id,
+        );
+
+        let page = store.query_sessions(&SessionQuery::default()).unwrap();
+        let ids: Vec<_> = page.sessions.iter().map(|s| s.id.as_str()).collect();
+        assert_eq!(ids, [good.id.as_str()]);
+        let skipped: Vec<_> = page.skipped.iter().map(|r| r.id.as_str()).collect();
+        assert_eq!(skipped, [bad.id.as_str(), bad_break.id.as_str()]);
+        assert_eq!(
+            page.skipped[0].error,
+            "start \"last tuesday\" is not a valid timestamp"
+        );
+
+        let error = store.get_session(&bad.id).unwrap_err();
+        assert_eq!(error.code(), "storage");
+
+        // A row that can't be read can still be deleted
+        store.delete_session(&bad.id).unwrap();
+        assert_eq!(
+            store
+                .query_sessions(&SessionQuery::default())
+                .unwrap()
+                .skipped
+                .len(),
+            1
+        );
+    }
+
+    #[test]
+    fn integrity_check_reports_and_repairs() {
+        let dir = TempDir::new();
+        let store = SessionStore::new(dir.path().join("store.db")).unwrap();
+        let sqlite_time = saved(&store, 8, 9, &[]);
+        // The break at 10 was never resumed before the next one at 11
+        let unpaired = saved(&store, 9, 12, &[(10, None), (11, Some(12))]);
+        let stale = saved(&store, 13, 14, &[]);
+        let backwards = saved(&store, 15, 16, &[]);
+        corrupt(
+            &store,
+            "UPDATE sessions SET start = '2025-03-10 08:00:00' WHERE id = ?1",
+            &sqlite_time.id,
+        );
+        corrupt(
+            &store,
+            "UPDATE sessions SET total_seconds = 60 WHERE id = ?1",
+            &stale.id,
+        );
+        corrupt(
+            &store,
+            "UPDATE sessions SET end = '2025-03-10T14:00:00+00:00' WHERE id = ?1",
+            &backwards.id,
+        );
+
+        let found = |report: &IntegrityReport| -> Vec<(IntegrityIssueKind, String, bool)> {
+            report
+                .issues
+                .iter()
+                .map(|issue| (issue.kind, issue.id.clone(), issue.fixed))
+                .collect()
+        };
+        let open_break = unpaired.breaks[0].id.clone();
+
+        let report = store.check_integrity(false).unwrap();
+        assert_eq!(report.sessions_checked, 4);
+        assert_eq!(report.breaks_checked, 2);
+        assert_eq!(
+            found(&report),
+            [
+                (
+                    IntegrityIssueKind::UnparsableTimestamp,
+                    sqlite_time.id.clone(),
+                    false
+                ),
+                (
+                    IntegrityIssueKind::MismatchedBreak,
+                    open_break.clone(),
+                    false
+                ),
+                (IntegrityIssueKind::StaleTotal, unpaired.id.clone(), false),
+                (IntegrityIssueKind::StaleTotal, stale.id.clone(), false),
+                (
+                    IntegrityIssueKind::NegativeDuration,
+                    backwards.id.clone(),
+                    false
+                ),
+            ]
+        );
+        // Only reporting leaves the rows alone
+        assert!(store.get_session(&sqlite_time.id).is_err());
+
+        let report = store.check_integrity(true).unwrap();
+        assert_eq!(
+            found(&report),
+            [
+                (
+                    IntegrityIssueKind::UnparsableTimestamp,
+                    sqlite_time.id.clone(),
+                    true
+                ),
+                (IntegrityIssueKind::MismatchedBreak, open_break, true),
+                (IntegrityIssueKind::StaleTotal, unpaired.id.clone(), true),
+                (IntegrityIssueKind::StaleTotal, stale.id.clone(), true),
+                (
+                    IntegrityIssueKind::NegativeDuration,
+                    backwards.id.clone(),
+                    false
+                ),
+            ]
+        );
+
+        assert_eq!(
+            store.get_session(&sqlite_time.id).unwrap().unwrap().start,
+            at(8, 0)
+        );
+        let unpaired = store.get_session(&unpaired.id).unwrap().unwrap();
+        assert_eq!(unpaired.breaks[0].ended_at, Some(at(11, 0)));
+        assert_eq!(unpaired.total_seconds, 3600);
+        assert_eq!(
+            store.get_session(&stale.id).unwrap().unwrap().total_seconds,
+            3600
+        );
+
+        // What couldn't be fixed is all that's left
+        let report = store.check_integrity(false).unwrap();
+        assert_eq!(
+            found(&report),
+            [(IntegrityIssueKind::NegativeDuration, backwards.id, false)]
+        );
+    }
+
+    fn manual(store: &SessionStore, start: DateTime<Utc>, end: DateTime<Utc>) -> Session {
+        let mut session = Session::new(None);
+        session.start = start;
+        session.end = Some(end);
+        store.create_manual_session(session).unwrap()
+    }
+
+    #[test]
+    fn projects_and_clients_are_stored_and_summarized() {
+        let dir = TempDir::new();
+        let store = utc_store(&dir);
+
+        let acme = Client::new("Acme".to_string());
+        store.save_client(&acme).unwrap();
+        let website = Project::new("Website".to_string(), Some(acme.id.clone()));
+        let internal = Project::new("internal".to_string(), None);
+        store.save_project(&website).unwrap();
+        store.save_project(&internal).unwrap();
+        let orphan = Project::new("Orphan".to_string(), Some("missing".to_string()));
+        let error = store.save_project(&orphan).unwrap_err();
+        assert_eq!(error.to_string(), "Client not found");
+        assert_eq!(error.code(), "validation");
+
+        let names: Vec<_> = store
+            .get_all_projects()
+            .unwrap()
+            .rows
+            .into_iter()
+            .map(|p| p.name)
+            .collect();
+        assert_eq!(names, ["internal", "Website"]);
+
+        let add = |project_id: Option<&str>, start, end| {
+            let mut session = Session::new(project_id.map(str::to_string));
+            session.start = at(start, 0);
+            session.end = Some(at(end, 0));
+            store.create_manual_session(session)
+        };
+        add(Some(&website.id), 9, 12).unwrap();
+        let internal_session = add(Some(&internal.id), 13, 14).unwrap();
+        add(None, 15, 17).unwrap();
+        assert_eq!(
+            add(Some("missing"), 18, 19).unwrap_err().code(),
+            "not_found"
+        );
+
+        let breakdown: Vec<_> = store
+            .get_monthly_summary(2025, 3)
+            .unwrap()
+            .project_breakdown
+            .into_iter()
+            .map(|p| (p.project_name, p.client_name, p.total_seconds))
+            .collect();
+        assert_eq!(
+            breakdown,
+            [
+                (
+                    Some("Website".to_string()),
+                    Some("Acme".to_string()),
+                    3 * 3600
+                ),
+                (None, None, 2 * 3600),
+                (Some("internal".to_string()), None, 3600),
+            ]
+        );
+
+        // Deleting a client detaches its projects
+        store.delete_client(&acme.id).unwrap();
+        assert!(store.get_client(&acme.id).unwrap().is_none());
+        assert_eq!(
+            store.get_project(&website.id).unwrap().unwrap().client_id,
+            None
+        );
+        assert_eq!(
+            store.delete_client(&acme.id).unwrap_err().code(),
+            "not_found"
+        );
+
+        // Deleting a project keeps its sessions
+        store.delete_project(&internal.id).unwrap();
+        let session = store.get_session(&internal_session.id).unwrap().unwrap();
+        assert_eq!(session.project_id, None);
+    }
+
+    #[test]
+    fn tags_are_normalized_and_filter_on_all_of_them() {
+        let dir = TempDir::new();
+        let store = utc_store(&dir);
+
+        let tags = |names: &[&str]| names.iter().map(|t| t.to_string()).collect::<Vec<_>>();
+        assert_eq!(
+            normalize_tags(&tags(&["#Review", " review ", "", "#", "deploy"])),
+            ["deploy", "Review"]
+        );
+
+        let tagged = |start, names: &[&str]| {
+            let mut session = Session::new(None);
+            session.start = at(start, 0);
+            session.end = Some(at(start + 1, 0));
+            session.tags = tags(names);
+            store.save_session(&session).unwrap();
+            session.id
+        };
+        let both = tagged(9, &["client-a", "#review"]);
+        let review = tagged(11, &["Review"]);
+        tagged(13, &["client-a"]);
+
+        assert_eq!(
+            store.get_session(&both).unwrap().unwrap().tags,
+            ["client-a", "review"]
+        );
+        assert_eq!(store.get_all_tags().unwrap(), ["client-a", "review"]);
+
+        let matching = |filter: &[&str]| -> Vec<String> {
+            let mut ids: Vec<_> = store
+                .get_all_sessions(&tags(filter))
+                .unwrap()
+                .rows
+                .into_iter()
+                .map(|s| s.id)
+                .collect();
+            ids.sort();
+            ids
+        };
+        let mut reviewed = vec![both.clone(), review];
+        reviewed.sort();
+        assert_eq!(matching(&["REVIEW"]), reviewed);
+        assert_eq!(matching(&["#review", "client-a"]), [both]);
+        assert_eq!(matching(&[]).len(), 3);
+    }
+
+    #[test]
+    fn manual_sessions_are_validated_and_totalled() {
+        let dir = TempDir::new();
+        let store = utc_store(&dir);
+
+        let mut session = Session::new(None);
+        session.start = at(9, 0);
+        session.end = Some(at(12, 0));
+        let mut lunch = Break::new("", at(10, 0));
+        lunch.ended_at = Some(at(10, 30));
+        session.breaks.push(lunch);
+        // Whatever total the caller sends is recomputed from the times
+        session.total_seconds = 1;
+        let mut session = store.create_manual_session(session).unwrap();
+        assert_eq!(session.total_seconds, 150 * 60);
+        assert_eq!(session.breaks[0].session_id, session.id);
+        assert!(!session.breaks[0].id.is_empty());
+
+        session.end = Some(at(13, 0));
+        session.total_seconds = 0;
+        let updated = store.update_session(session.clone()).unwrap();
+        assert_eq!(updated.total_seconds, 210 * 60);
+        assert_eq!(
+            store
+                .get_session(&session.id)
+                .unwrap()
+                .unwrap()
+                .total_seconds,
+            210 * 60
+        );
+
+        let mut overlapping = Session::new(None);
+        overlapping.start = at(12, 30);
+        overlapping.end = Some(at(14, 0));
+        assert_eq!(
+            store
+                .create_manual_session(overlapping.clone())
+                .unwrap_err()
+                .code(),
+            "conflict"
+        );
+        // Touching sessions don't overlap
+        overlapping.start = at(13, 0);
+        store.create_manual_session(overlapping).unwrap();
+
+        let mut backwards = Session::new(None);
+        backwards.start = at(16, 0);
+        backwards.end = Some(at(15, 0));
+        assert_eq!(
+            store.create_manual_session(backwards).unwrap_err().code(),
+            "validation"
+        );
+
+        // Running sessions can't be edited or deleted
+        let running = Session::new(None);
+        store.save_session(&running).unwrap();
+        let mut edited = running.clone();
+        edited.end = Some(Utc::now());
+        assert_eq!(store.update_session(edited).unwrap_err().code(), "conflict");
+        assert_eq!(
+            store.delete_session(&running.id).unwrap_err().code(),
+            "conflict"
+        );
+    }
+
+    #[test]
+    fn session_queries_page_through_every_match() {
+        let dir = TempDir::new();
+        let store = utc_store(&dir);
+        let starts: Vec<_> = (8..15)
+            .map(|hour| saved(&store, hour, hour + 1, &[]).start)
+            .collect();
+
+        let mut query = SessionQuery {
+            limit: Some(3),
+            sort: SessionSort::StartAsc,
+            ..SessionQuery::default()
+        };
+        let mut seen = Vec::new();
+        let mut pages = 0;
+        loop {
+            let page = store.query_sessions(&query).unwrap();
+            assert_eq!(page.total_count, 7);
+            seen.extend(page.sessions.iter().map(|s| s.start));
+            pages += 1;
+            match page.next_offset {
+                Some(offset) => query.offset = Some(offset),
+                None => break,
+            }
+        }
+        assert_eq!(pages, 3);
+        assert_eq!(seen, starts);
+
+        // Past the end is an empty last page
+        query.offset = Some(10);
+        let page = store.query_sessions(&query).unwrap();
+        assert!(page.sessions.is_empty());
+        assert_eq!(page.next_offset, None);
+    }
+
+    #[test]
+    fn session_query_filters_combine() {
+        let dir = TempDir::new();
+        let store = utc_store(&dir);
+        let project = Project::new("Website".to_string(), None);
+        store.save_project(&project).unwrap();
+
+        let add = |start, end, project_id: Option<&str>| {
+            let mut session = Session::new(project_id.map(str::to_string));
+            session.start = at(start, 0);
+            session.end = Some(at(end, 0));
+            store.create_manual_session(session).unwrap().id
+        };
+        let short = add(8, 9, Some(&project.id));
+        let long = add(9, 12, Some(&project.id));
+        let longest = add(13, 17, Some(&project.id));
+        add(17, 19, None);
+        let running = Session::new(Some(project.id.clone()));
+        store.save_session(&running).unwrap();
+
+        let ids = |query: SessionQuery| -> Vec<String> {
+            let page = store.query_sessions(&query).unwrap();
+            assert_eq!(page.total_count, page.sessions.len());
+            page.sessions.into_iter().map(|s| s.id).collect()
+        };
+
+        let in_project_on_the_day = SessionQuery {
+            from: Some(at(8, 0)),
+            to: Some(at(17, 0)),
+            project_id: Some(project.id.clone()),
+            status: Some(SessionStatus::Closed),
+            ..SessionQuery::default()
+        };
+        assert_eq!(
+            ids(in_project_on_the_day.clone()),
+            [longest.as_str(), long.as_str(), short.as_str()]
+        );
+        assert_eq!(
+            ids(SessionQuery {
+                min_duration_seconds: Some(2 * 3600),
+                max_duration_seconds: Some(3 * 3600),
+                ..in_project_on_the_day.clone()
+            }),
+            [long.as_str()]
+        );
+        assert_eq!(
+            ids(SessionQuery {
+                sort: SessionSort::DurationDesc,
+                ..in_project_on_the_day.clone()
+            }),
+            [longest.as_str(), long.as_str(), short.as_str()]
+        );
+        assert_eq!(
+            ids(SessionQuery {
+                status: Some(SessionStatus::Open),
+                project_id: Some(project.id.clone()),
+                ..SessionQuery::default()
+            }),
+            [running.id.as_str()]
+        );
+    }
+
+    #[test]
+    fn weeks_straddling_the_month_count_in_full_and_share_their_overtime() {
+        let dir = TempDir::new();
+        let store = utc_store(&dir);
+        let day = |month, day, hours| {
+            let start = Utc.with_ymd_and_hms(2025, month, day, 6, 0, 0).unwrap();
+            manual(&store, start, start + ChronoDuration::hours(hours));
+        };
+        day(3, 24, 8);
+        // Monday 31 March to Thursday 3 April: 48 hours, 8 of them overtime
+        day(3, 31, 12);
+        day(4, 1, 12);
+        day(4, 2, 12);
+        day(4, 3, 12);
+
+        let march = store.get_monthly_summary(2025, 3).unwrap();
+        assert_eq!(march.total_seconds, 20 * 3600);
+
+        let weeks: Vec<_> = march
+            .weekly_breakdown
+            .iter()
+            .map(|w| (w.week_start.to_string(), w.week_end.to_string()))
+            .collect();
+        assert_eq!(
+            weeks,
+            [
+                ("2025-03-24".to_string(), "2025-03-30".to_string()),
+                ("2025-03-31".to_string(), "2025-04-06".to_string()),
+            ]
+        );
+
+        let straddling = &march.weekly_breakdown[1];
+        assert_eq!(straddling.total_hours, 48.0);
+        assert_eq!(straddling.in_range_hours, 12.0);
+        assert_eq!(straddling.overtime_hours, 8.0);
+        assert_eq!(straddling.session_count, 4);
+        let days: Vec<_> = straddling
+            .days
+            .iter()
+            .map(|d| (d.total_seconds / 3600, d.in_range))
+            .collect();
+        assert_eq!(
+            days,
+            [
+                (12, true),
+                (12, false),
+                (12, false),
+                (12, false),
+                (0, false),
+                (0, false),
+                (0, false)
+            ]
+        );
+
+        // A quarter of the week fell in March, so March gets a quarter of its overtime
+        assert_eq!(march.overtime_hours, 2.0);
+        assert_eq!(march.regular_hours, 18.0);
+        assert_eq!(march.weighted_hours, 8.0 + (40.0 + 8.0 * 1.5) / 4.0);
+
+        let april = store.get_monthly_summary(2025, 4).unwrap();
+        assert_eq!(april.weekly_breakdown.len(), 1);
+        assert_eq!(april.overtime_hours, 6.0);
+    }
+
+    const BENCH_SESSIONS: usize = 100_000;
+
+    /// Fill the store with `count` ended one-hour sessions, one per two hours
+    /// going back from now, each with a 10 minute break and every tenth tagged.
+    fn populate(store: &SessionStore, count: usize) {
+        let mut conn = store.get_connection().unwrap();
+        let tx = conn.transaction().unwrap();
+        let now = Utc::now();
+
+        for i in 0..count {
+            let start = now - ChronoDuration::hours(2 * (i as i64 + 1));
+            let mut session = Session::new(None);
+            session.start = start;
+            session.end = Some(start + ChronoDuration::hours(1));
+            let mut b = Break::new(&session.id, start + ChronoDuration::minutes(20));
+            b.ended_at = Some(start + ChronoDuration::minutes(30));
+            session.breaks.push(b);
+            session.total_seconds = session.calculate_total_seconds();
+
+            tx.execute(
+                "INSERT INTO sessions (id, start, end, total_seconds) VALUES (?1, ?2, ?3, ?4)",
+                params![
+                    session.id,
+                    session.start.to_rfc3339(),
+                    session.end.map(|dt| dt.to_rfc3339()),
+                    session.total_seconds,
+                ],
+            )
+            .unwrap();
+            tx.execute(
+                "INSERT INTO session_breaks (id, session_id, started_at, ended_at) VALUES (?1, ?2, ?3, ?4)",
+                params![
+                    session.breaks[0].id,
+                    session.id,
+                    session.breaks[0].started_at.to_rfc3339(),
+                    session.breaks[0].ended_at.map(|dt| dt.to_rfc3339()),
+                ],
+            )
+            .unwrap();
+            if i % 10 == 0 {
+                tx.execute("INSERT OR IGNORE INTO tags (name) VALUES ('bench')", [])
+                    .unwrap();
+                tx.execute(
+                    "INSERT INTO session_tags (session_id, tag_id)
+                     SELECT ?1, id FROM tags WHERE name = 'bench'",
+                    params![session.id],
+                )
+                .unwrap();
+            }
+        }
+
+        tx.commit().unwrap();
+    }
+
+    /// Latency of the hot store operations on a 100k-session database. Ignored
+    /// by default as populating takes a while; run with
+    /// `cargo test --release bench_100k_sessions -- --ignored --nocapture`
+    #[test]
+    #[ignore]
+    fn bench_100k_sessions() {
+        let dir = TempDir::new();
+        let store = SessionStore::new(dir.path().join("time-tracker.db")).unwrap();
+        populate(&store, BENCH_SESSIONS);
+
+        let runs = 5;
+        let started = Instant::now();
+        for _ in 0..runs {
+            let sessions = store.get_all_sessions(&[]).unwrap().rows;
+            assert_eq!(sessions.len(), BENCH_SESSIONS);
+        }
+        println!(
+            "get_all_sessions ({} sessions): {:?} per call",
+            BENCH_SESSIONS,
+            started.elapsed() / runs
+        );
+
+        let started = Instant::now();
+        let tagged = store.get_all_sessions(&["bench".to_string()]).unwrap().rows;
+        println!(
+            "get_all_sessions filtered by tag ({} matches): {:?}",
+            tagged.len(),
+            started.elapsed()
+        );
+
+        // Simulate a running session being paused and resumed repeatedly
+        let mut session = Session::new(None);
+        let saves = 1_000u32;
+        let started = Instant::now();
+        for i in 0..saves {
+            if i % 2 == 0 {
+                session.breaks.push(Break::new(&session.id, Utc::now()));
+            } else if let Some(b) = session.breaks.last_mut() {
+                b.ended_at = Some(Utc::now());
+            }
+            store.save_session(&session).unwrap();
+        }
+        println!("save_session: {:?} per call", started.elapsed() / saves);
+    }
+
+    fn new_york_store(dir: &TempDir) -> SessionStore {
+        let store = SessionStore::new(dir.path().join("store.db")).unwrap();
+        store.save_timezone("America/New_York").unwrap();
+        store
+    }
+
+    #[test]
+    fn fall_back_day_summarizes_25_hours() {
+        let dir = TempDir::new();
+        let store = new_york_store(&dir);
+        // Local midnight to midnight, EDT to EST
+        manual(
+            &store,
+            Utc.with_ymd_and_hms(2025, 11, 2, 4, 0, 0).unwrap(),
+            Utc.with_ymd_and_hms(2025, 11, 3, 5, 0, 0).unwrap(),
+        );
+
+        let date = NaiveDate::from_ymd_opt(2025, 11, 2).unwrap();
+        let summary = store
+            .get_summary(
+                &SummaryRange::Day { date },
+                Granularity::Day,
+                OverlapMode::PerTimer,
+            )
+            .unwrap();
+        assert_eq!(summary.total_seconds, 25 * 3600);
+        assert_eq!(summary.breakdown.len(), 1);
+        assert_eq!(summary.breakdown[0].start, date);
+    }
+
+    #[test]
+    fn months_are_bucketed_by_local_date() {
+        let dir = TempDir::new();
+        let store = new_york_store(&dir);
+        // Halloween evening in New York, already November in UTC
+        let halloween = manual(
+            &store,
+            Utc.with_ymd_and_hms(2025, 11, 1, 1, 0, 0).unwrap(),
+            Utc.with_ymd_and_hms(2025, 11, 1, 3, 0, 0).unwrap(),
+        );
+
+        let october = store.get_sessions_for_month(2025, 10).unwrap().rows;
+        assert_eq!(october.len(), 1);
+        assert_eq!(october[0].id, halloween.id);
+        assert!(store
+            .get_sessions_for_month(2025, 11)
+            .unwrap()
+            .rows
+            .is_empty());
+
+        let summary = store.get_monthly_summary(2025, 10).unwrap();
+        assert_eq!(summary.total_seconds, 2 * 3600);
+        assert_eq!(summary.daily_breakdown[0].date, "2025-10-31");
+        assert_eq!(
+            store.get_monthly_summary(2025, 11).unwrap().total_seconds,
+            0
+        );
+    }
+
+    #[test]
+    fn running_sessions_count_up_to_now_and_are_flagged_in_progress() {
+        let dir = TempDir::new();
+        let store = utc_store(&dir);
+        let now = Utc::now();
+        let today = now.date_naive();
+        let midnight = today.and_hms_opt(0, 0, 0).unwrap().and_utc();
+        let two_days_ago = midnight - ChronoDuration::days(2);
+        manual(
+            &store,
+            two_days_ago,
+            two_days_ago + ChronoDuration::hours(1),
+        );
+
+        // Kept on today so the test holds just after midnight
+        let mut running = Session::new(None);
+        running.start = (now - ChronoDuration::hours(2)).max(midnight);
+        store.save_session(&running).unwrap();
+        let running_seconds = (now - running.start).num_seconds();
+
+        let range = SummaryRange::Custom {
+            start: two_days_ago.date_naive(),
+            end: today,
+        };
+        let days = store
+            .get_summary(&range, Granularity::Day, OverlapMode::PerTimer)
+            .unwrap();
+        let flags: Vec<_> = days
+            .breakdown
+            .iter()
+            .map(|d| (d.start, d.in_progress))
+            .collect();
+        assert_eq!(flags, [(two_days_ago.date_naive(), false), (today, true)]);
+        let worked_today = days.breakdown[1].total_seconds;
+        assert!((running_seconds..running_seconds + 5).contains(&worked_today));
+        assert_eq!(days.total_seconds, 3600 + worked_today);
+
+        let periods = store
+            .get_summary(&range, Granularity::Year, OverlapMode::PerTimer)
+            .unwrap();
+        let current = periods.breakdown.last().unwrap();
+        assert!(current.in_progress);
+        assert!(current.total_seconds >= running_seconds);
+
+        let month = store
+            .get_monthly_summary(today.year(), today.month())
+            .unwrap();
+        for day in &month.daily_breakdown {
+            assert_eq!(
+                day.in_progress,
+                day.date == today.to_string(),
+                "{}",
+                day.date
+            );
+        }
+        assert!(month.daily_breakdown.iter().any(|d| d.in_progress));
+    }
+
+    #[test]
+    fn lists_and_summaries_report_the_rows_they_leave_out() {
+        let dir = TempDir::new();
+        let store = utc_store(&dir);
+        saved(&store, 9, 10, &[]);
+        let bad = saved(&store, 11, 12, &[]);
+        let bad_break = saved(&store, 13, 15, &[(14, Some(14))]);
+        let client = Client::new("Acme".to_string());
+        store.save_client(&client).unwrap();
+        let project = Project::new("Website".to_string(), None);
+        store.save_project(&project).unwrap();
+        corrupt(
+            &store,
+            "UPDATE sessions SET end = 'around noon' WHERE id = ?1",
+            &bad.id,
+        );
+        corrupt(
+            &store,
+            "UPDATE session_breaks SET ended_at = 'soon' WHERE session_id = ?1",
+            &bad_break.id,
+        );
+        corrupt(
+            &store,
+            "UPDATE clients SET created_at = 'ages ago' WHERE id = ?1",
+            &client.id,
+        );
+        corrupt(
+            &store,
+            "UPDATE projects SET created_at = 'ages ago' WHERE id = ?1",
+            &project.id,
+        );
+
+        let skipped = |rows: &[SkippedRow]| -> Vec<(String, String)> {
+            rows.iter()
+                .map(|r| (r.table.clone(), r.id.clone()))
+                .collect()
+        };
+        let expected = [
+            ("sessions".to_string(), bad.id.clone()),
+            ("sessions".to_string(), bad_break.id.clone()),
+            ("projects".to_string(), project.id.clone()),
+            ("clients".to_string(), client.id.clone()),
+        ];
+
+        let summary = day_summary(&store);
+        assert_eq!(summary.total_seconds, 3600);
+        assert_eq!(skipped(&summary.skipped), expected);
+        let month = store.get_monthly_summary(2025, 3).unwrap();
+        assert_eq!(skipped(&month.skipped), expected);
+
+        let breaks = store
+            .get_breaks(at(0, 0), at(0, 0) + ChronoDuration::days(1))
+            .unwrap();
+        assert!(breaks.rows.is_empty());
+        assert_eq!(
+            skipped(&breaks.skipped),
+            [("session_breaks".to_string(), bad_break.breaks[0].id.clone())]
+        );
+        assert!(store.get_all_clients().unwrap().rows.is_empty());
+        assert!(store.get_all_projects().unwrap().rows.is_empty());
+
+        let sessions = [
+            ("sessions".to_string(), bad.id.clone()),
+            ("sessions".to_string(), bad_break.id.clone()),
+        ];
+        let all = store.get_all_sessions(&[]).unwrap();
+        assert_eq!(all.rows.len(), 1);
+        assert_eq!(skipped(&all.skipped), sessions);
+        let month = store.get_sessions_for_month(2025, 3).unwrap();
+        assert_eq!(month.rows.len(), 1);
+        assert_eq!(skipped(&month.skipped), sessions);
 
-        Ok(sessions)
+        // A session overlapping the unreadable one is refused, naming it
+        let mut overlapping = Session::new(None);
+        overlapping.start = at(11, 15);
+        overlapping.end = Some(at(11, 45));
+        let err = store.create_manual_session(overlapping).unwrap_err();
+        assert_eq!(err.code(), "storage");
+        assert!(err.to_string().contains(&bad.id));
     }
 }
@@ -1,95 +1,209 @@
+use crate::commands::AppState;
+use crate::models::{TimerEvent, TimerState};
+use crate::timer_manager::TimerManager;
 use tauri::{
-    menu::{Menu, MenuItem},
+    menu::{IsMenuItem, Menu, MenuItem, PredefinedMenuItem, Submenu},
     tray::TrayIconBuilder,
-    Emitter, Manager, Runtime,
+    AppHandle, Emitter, Manager, Runtime,
 };
+use tokio::sync::broadcast::error::RecvError;
 
-pub fn create_tray<R: Runtime>(app: &tauri::AppHandle<R>) -> tauri::Result<()> {
-    let quit_item = MenuItem::with_id(app, "quit", "Quit Time Tracker", true, None::<&str>)?;
-    let show_item = MenuItem::with_id(app, "show", "Show Window", true, None::<&str>)?;
-    let start_item = MenuItem::with_id(app, "start", "Start Timer", true, None::<&str>)?;
+/// Reasons offered in the tray's pause submenu, recorded on the break
+const PAUSE_REASONS: [&str; 3] = ["Lunch", "Meeting", "Personal"];
+
+/// Prefix of the pause submenu item ids, followed by the reason
+const PAUSE_REASON_PREFIX: &str = "pause_reason:";
 
-    let menu = Menu::with_items(
-        app,
-        &[&show_item, &start_item, &quit_item],
-    )?;
+pub fn create_tray<R: Runtime>(app: &AppHandle<R>, state: &TimerState) -> tauri::Result<()> {
+    let menu = build_menu(app, state)?;
 
     TrayIconBuilder::with_id("main")
         .menu(&menu)
         .icon(app.default_window_icon().unwrap().clone())
+        .tooltip("Time Tracker")
         .on_menu_event(move |app, event| {
             println!("Tray menu event: {}", event.id.as_ref());
-            match event.id.as_ref() {
-                "quit" => {
-                    app.exit(0);
-                }
-                "show" => {
-                    if let Some(window) = app.get_webview_window("main") {
-                        let _ = window.show();
-                        let _ = window.set_focus();
-                    }
-                }
-                "start" => {
-                    println!("Emitting tray-start-timer event");
-                    let _ = app.emit("tray-start-timer", ());
-                }
-                "pause" => {
-                    println!("Emitting tray-pause-timer event");
-                    let _ = app.emit("tray-pause-timer", ());
-                }
-                "resume" => {
-                    println!("Emitting tray-resume-timer event");
-                    let _ = app.emit("tray-resume-timer", ());
-                }
-                "end" => {
-                    println!("Emitting tray-end-timer event");
-                    let _ = app.emit("tray-end-timer", ());
-                }
-                _ => {
-                    println!("Unknown tray event: {}", event.id.as_ref());
-                }
-            }
+            handle_menu_event(app, event.id.as_ref());
         })
         .build(app)?;
 
     Ok(())
 }
 
-pub fn update_tray_menu<R: Runtime>(
-    app: &tauri::AppHandle<R>,
-    is_running: bool,
-    is_paused: bool,
-    _elapsed_seconds: i64,
-) -> tauri::Result<()> {
-    println!("Updating tray menu - is_running: {}, is_paused: {}", is_running, is_paused);
-    
-    let quit_item = MenuItem::with_id(app, "quit", "Quit Time Tracker", true, None::<&str>)?;
-    let show_item = MenuItem::with_id(app, "show", "Show Window", true, None::<&str>)?;
+/// Act on a tray menu click. Timer actions go straight to the `TimerManager`,
+/// so they work with the window closed; the tray and any open window pick up
+/// the result from `follow_timer`.
+fn handle_menu_event<R: Runtime>(app: &AppHandle<R>, id: &str) {
+    let state = app.state::<AppState>();
+    let timer = &state.timer_manager;
+    let result = match id {
+        "quit" => {
+            app.exit(0);
+            return;
+        }
+        "show" => {
+            if let Some(window) = app.get_webview_window("main") {
+                let _ = window.show();
+                let _ = window.set_focus();
+            }
+            return;
+        }
+        "start" => timer.start_session(None, None).map(|_| ()),
+        "pause" => timer.pause_session(None).map(|_| ()),
+        "resume" => timer.resume_session().map(|_| ()),
+        "end" => timer.end_session().map(|_| ()),
+        "start_pomodoro" => timer.start_pomodoro(None).map(|_| ()),
+        "stop_pomodoro" => timer.stop_pomodoro(),
+        _ => match id.strip_prefix(PAUSE_REASON_PREFIX) {
+            Some(reason) => timer.pause_session(Some(reason.to_string())).map(|_| ()),
+            None => return,
+        },
+    };
 
-    let menu = if !is_running {
-        println!("Creating menu with Start Timer");
-        let start_item = MenuItem::with_id(app, "start", "Start Timer", true, None::<&str>)?;
-        Menu::with_items(app, &[&show_item, &start_item, &quit_item])?
-    } else if is_paused {
-        println!("Creating menu with Resume Timer");
-        let resume_item = MenuItem::with_id(app, "resume", "Resume Timer", true, None::<&str>)?;
-        let end_item = MenuItem::with_id(app, "end", "End Session", true, None::<&str>)?;
-        Menu::with_items(app, &[&show_item, &resume_item, &end_item, &quit_item])?
+    if let Err(e) = result {
+        eprintln!("Tray action {} failed: {}", id, e);
+    }
+}
+
+/// Keep the tray and windows in step with the timer for the lifetime of the
+/// app. Every event is forwarded to the windows as `timer-event`; on changes
+/// other than ticks the menu is rebuilt and the new state sent as
+/// `timer-state-changed`, and the tray title follows the elapsed time.
+pub async fn follow_timer<R: Runtime>(app: AppHandle<R>, timer: TimerManager) {
+    let mut events = timer.subscribe();
+
+    loop {
+        let event = match events.recv().await {
+            Ok(event) => event,
+            // Whatever was missed, the state read below is current
+            Err(RecvError::Lagged(_)) => TimerEvent::Changed,
+            Err(RecvError::Closed) => break,
+        };
+
+        if let TimerEvent::Tick { state } = &event {
+            show_elapsed(&app, state);
+        } else {
+            let state = timer.get_state();
+            if let Err(e) = update_tray_menu(&app, &state) {
+                eprintln!("Failed to update tray menu: {}", e);
+            }
+            show_elapsed(&app, &state);
+            let _ = app.emit("timer-state-changed", &state);
+        }
+        let _ = app.emit("timer-event", &event);
+    }
+}
+
+/// Put the running session's elapsed time in the tray title and tooltip
+fn show_elapsed<R: Runtime>(app: &AppHandle<R>, state: &TimerState) {
+    let Some(tray) = app.tray_by_id("main") else {
+        return;
+    };
+
+    let (title, tooltip) = if !state.is_running {
+        (None, "Time Tracker".to_string())
     } else {
-        println!("Creating menu with Pause Timer");
-        let pause_item = MenuItem::with_id(app, "pause", "Pause Timer", true, None::<&str>)?;
-        let end_item = MenuItem::with_id(app, "end", "End Session", true, None::<&str>)?;
-        Menu::with_items(app, &[&show_item, &pause_item, &end_item, &quit_item])?
+        let elapsed = format_elapsed(state.elapsed_seconds);
+        let tooltip = match &state.pomodoro {
+            Some(pomodoro) => format!("Time Tracker - {} ({})", elapsed, pomodoro.phase.label()),
+            None if state.is_paused => format!("Time Tracker - {} (paused)", elapsed),
+            None => format!("Time Tracker - {}", elapsed),
+        };
+        (Some(elapsed), tooltip)
     };
 
+    let _ = tray.set_title(title.as_deref());
+    let _ = tray.set_tooltip(Some(tooltip));
+}
+
+pub(crate) fn format_elapsed(seconds: i64) -> String {
+    format!("{}:{:02}:{:02}", seconds / 3600, (seconds % 3600) / 60, seconds % 60)
+}
+
+pub fn update_tray_menu<R: Runtime>(app: &AppHandle<R>, state: &TimerState) -> tauri::Result<()> {
+    let menu = build_menu(app, state)?;
+
     // Get the main tray icon by ID and update it
     if let Some(tray) = app.tray_by_id("main") {
-        println!("Tray found, setting new menu");
         tray.set_menu(Some(menu))?;
-        println!("Tray menu updated successfully");
     } else {
         println!("Warning: Tray not found!");
     }
 
     Ok(())
 }
+
+fn build_menu<R: Runtime>(app: &AppHandle<R>, state: &TimerState) -> tauri::Result<Menu<R>> {
+    println!(
+        "Building tray menu - is_running: {}, is_paused: {}",
+        state.is_running, state.is_paused
+    );
+
+    let quit_item = MenuItem::with_id(app, "quit", "Quit Time Tracker", true, None::<&str>)?;
+    let show_item = MenuItem::with_id(app, "show", "Show Window", true, None::<&str>)?;
+    let end_item = MenuItem::with_id(app, "end", "End Session", true, None::<&str>)?;
+
+    let mut items: Vec<Box<dyn IsMenuItem<R>>> = vec![Box::new(show_item)];
+
+    if let Some(pomodoro) = &state.pomodoro {
+        let phase_item = MenuItem::with_id(
+            app,
+            "pomodoro_phase",
+            format!(
+                "{} {}/{} - until {}",
+                pomodoro.phase.label(),
+                pomodoro.cycle,
+                pomodoro.settings.cycles_before_long_break,
+                crate::clock_time(app, pomodoro.phase_ends_at),
+            ),
+            false,
+            None::<&str>,
+        )?;
+        let stop_item =
+            MenuItem::with_id(app, "stop_pomodoro", "Stop Pomodoro", true, None::<&str>)?;
+        items.push(Box::new(phase_item));
+        items.push(Box::new(stop_item));
+        items.push(Box::new(end_item));
+    } else if !state.is_running {
+        println!("Creating menu with Start Timer");
+        let start_item = MenuItem::with_id(app, "start", "Start Timer", true, None::<&str>)?;
+        let pomodoro_item =
+            MenuItem::with_id(app, "start_pomodoro", "Start Pomodoro", true, None::<&str>)?;
+        items.push(Box::new(start_item));
+        items.push(Box::new(pomodoro_item));
+    } else if state.is_paused {
+        println!("Creating menu with Resume Timer");
+        let resume_item = MenuItem::with_id(app, "resume", "Resume Timer", true, None::<&str>)?;
+        items.push(Box::new(resume_item));
+        items.push(Box::new(end_item));
+    } else {
+        println!("Creating menu with Pause Timer");
+        let pause_item = pause_submenu(app)?;
+        let pomodoro_item =
+            MenuItem::with_id(app, "start_pomodoro", "Start Pomodoro", true, None::<&str>)?;
+        items.push(Box::new(pause_item));
+        items.push(Box::new(pomodoro_item));
+        items.push(Box::new(end_item));
+    }
+
+    items.push(Box::new(quit_item));
+    let item_refs: Vec<&dyn IsMenuItem<R>> = items.iter().map(|item| item.as_ref()).collect();
+    Menu::with_items(app, &item_refs)
+}
+
+/// "Pause Timer" with a plain pause first and then one entry per reason
+fn pause_submenu<R: Runtime>(app: &AppHandle<R>) -> tauri::Result<Submenu<R>> {
+    let submenu = Submenu::with_id(app, "pause_menu", "Pause Timer", true)?;
+    submenu.append(&MenuItem::with_id(app, "pause", "Pause", true, None::<&str>)?)?;
+    submenu.append(&PredefinedMenuItem::separator(app)?)?;
+    for reason in PAUSE_REASONS {
+        submenu.append(&MenuItem::with_id(
+            app,
+            format!("{}{}", PAUSE_REASON_PREFIX, reason),
+            reason,
+            true,
+            None::<&str>,
+        )?)?;
+    }
+    Ok(submenu)
+}
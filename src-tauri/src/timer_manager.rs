@@ -1,12 +1,98 @@
-use crate::models::Session;
-use crate::session_store::SessionStore;
-use anyhow::{Context, Result};
-use chrono::Utc;
+use crate::error::{fail, OrError, Result, TimelyError};
+use crate::intervals;
+use crate::models::{
+    Break, BudgetEvent, BudgetEventKind, BudgetExpiryAction, GapResolution, IdleEvent,
+    IdleInterval, IdleResolution, IdleSettings, PomodoroPhase, PomodoroState, PomodoroTransition,
+    RunningTimer, Session, SessionBudget, SessionGap, TimerEvent, TimerState, DEFAULT_TIMER_NAME,
+};
+use crate::session_store::{normalize_tags, SessionStore};
+use chrono::{DateTime, Duration, Utc};
+use std::collections::BTreeMap;
 use std::sync::{Arc, Mutex};
+use tokio::sync::broadcast;
+use tokio::time::MissedTickBehavior;
 
+/// Reason recorded on breaks the timer inserts when the user goes idle
+pub const IDLE_BREAK_REASON: &str = "Idle";
+
+/// Reason recorded on breaks taken while the screen is locked
+pub const LOCK_BREAK_REASON: &str = "Screen locked";
+
+/// Reason recorded on breaks covering a shutdown or sleep gap
+pub const GAP_BREAK_REASON: &str = "Away";
+
+/// Reason recorded on the break inserted when a budget runs out
+pub const BUDGET_BREAK_REASON: &str = "Budget used up";
+
+/// How often the running session's budget is checked
+pub const BUDGET_TICK: std::time::Duration = std::time::Duration::from_secs(1);
+
+/// How often a running Pomodoro checks whether its phase is over
+pub const POMODORO_TICK: std::time::Duration = std::time::Duration::from_secs(1);
+
+/// How often a `Tick` event is published while any timer runs
+pub const EVENT_TICK: std::time::Duration = std::time::Duration::from_secs(1);
+
+/// Events a slow subscriber can fall behind by before it misses some
+const EVENT_BACKLOG: usize = 64;
+
+/// How often the running session's heartbeat is persisted
+pub const HEARTBEAT_INTERVAL: std::time::Duration = std::time::Duration::from_secs(30);
+
+/// Silence longer than this between heartbeats means the app wasn't running
+/// or the machine was asleep
+fn gap_threshold() -> Duration {
+    Duration::minutes(5)
+}
+
+#[derive(Debug, Default)]
+struct BudgetAlerts {
+    warned: bool,
+    expired: bool,
+}
+
+impl BudgetAlerts {
+    /// The alerts a session picked up from a previous run will already have
+    /// raised, going by the time it had worked by `seen_until`. Alerts only
+    /// live in memory, so without this they would all be raised again.
+    fn raised_by(session: &Session, seen_until: DateTime<Utc>) -> Self {
+        let Some(budget) = &session.budget else {
+            return Self::default();
+        };
+        let worked_seconds: i64 = intervals::worked_intervals(session, seen_until)
+            .iter()
+            .map(|(start, end)| (*end - *start).num_seconds())
+            .sum();
+        Self {
+            warned: worked_seconds >= budget.seconds - budget.warning_seconds,
+            expired: worked_seconds >= budget.seconds,
+        }
+    }
+}
+
+/// Runs the primary timer, the one the window, tray and shortcuts act on and
+/// that idle, lock, gap, Pomodoro and budget handling apply to, alongside any
+/// number of other named timers (a long build, an on-call shift) that just
+/// keep time.
 #[derive(Clone)]
 pub struct TimerManager {
+    /// The primary timer's session
     current_session: Arc<Mutex<Option<Session>>>,
+    /// Sessions on the other timers, by timer name. Locked after `current_session`.
+    others: Arc<Mutex<BTreeMap<String, Session>>>,
+    /// Idle time awaiting the user's decision. Locked after `current_session`.
+    pending_idle: Arc<Mutex<Option<IdleInterval>>>,
+    /// Shutdown or sleep gaps awaiting the user's decision, by session id.
+    /// Locked after `current_session` and before `others`.
+    pending_gaps: Arc<Mutex<BTreeMap<String, SessionGap>>>,
+    /// Running Pomodoro, if any. Locked after `current_session`.
+    pomodoro: Arc<Mutex<Option<PomodoroState>>>,
+    /// Budget events already raised for the running session. Locked after `current_session`.
+    budget_alerts: Arc<Mutex<BudgetAlerts>>,
+    /// Break started by locking the screen, ended on unlock. Locked after `current_session`.
+    lock_break_id: Arc<Mutex<Option<String>>>,
+    /// Every change to the timers, as it happens
+    events: broadcast::Sender<TimerEvent>,
     store: Arc<SessionStore>,
 }
 
@@ -14,6 +100,13 @@ impl TimerManager {
     pub fn new(store: Arc<SessionStore>) -> Result<Self> {
         let manager = Self {
             current_session: Arc::new(Mutex::new(None)),
+            others: Arc::new(Mutex::new(BTreeMap::new())),
+            pending_idle: Arc::new(Mutex::new(None)),
+            pending_gaps: Arc::new(Mutex::new(BTreeMap::new())),
+            pomodoro: Arc::new(Mutex::new(None)),
+            budget_alerts: Arc::new(Mutex::new(BudgetAlerts::default())),
+            lock_break_id: Arc::new(Mutex::new(None)),
+            events: broadcast::Sender::new(EVENT_BACKLOG),
             store,
         };
 
@@ -23,71 +116,805 @@ impl TimerManager {
         Ok(manager)
     }
 
+    /// Pick up the sessions left running by a previous run, putting the one
+    /// last made primary back on the primary timer. Each timer keeps its latest
+    /// session; older ones left open on the same timer, as databases from
+    /// before named timers can have, are ended where they were last seen. If
+    /// the app has been gone for a while the time since each session's last
+    /// heartbeat is held back as a gap for the user to settle.
     fn recover_active_session(&self) -> Result<()> {
-        if let Some(session) = self.store.get_active_session()? {
-            let mut current = self.current_session.lock().unwrap();
-            *current = Some(session);
+        // Latest first. Sessions that can't be read are left for the integrity
+        // check to report.
+        let sessions = self.store.get_active_sessions()?.rows;
+        let latest = sessions.first().map(|s| s.timer_name.clone());
+
+        let mut timers: BTreeMap<String, Session> = BTreeMap::new();
+        for mut session in sessions {
+            if timers.contains_key(&session.timer_name) {
+                let last_seen_at = last_seen(&session);
+                end_at(&mut session, last_seen_at);
+                self.store.save_session(&session)?;
+                eprintln!(
+                    "Ended session {} left open alongside a later one on the {} timer",
+                    session.id, session.timer_name
+                );
+                continue;
+            }
+            timers.insert(session.timer_name.clone(), session);
         }
+
+        let saved_primary = self.store.get_primary_timer()?;
+        let Some(primary_name) = saved_primary
+            .filter(|name| timers.contains_key(name))
+            .or(latest)
+        else {
+            return Ok(());
+        };
+
+        let now = Utc::now();
+        let mut current = self.current_session.lock().unwrap();
+        let mut pending = self.pending_gaps.lock().unwrap();
+        pending.extend(
+            timers
+                .values()
+                .filter_map(|session| detect_gap(session, now))
+                .map(|gap| (gap.session_id.clone(), gap)),
+        );
+
+        let session = timers
+            .remove(&primary_name)
+            .expect("primary timer was just found");
+        // Time in a gap wasn't seen by the last run, so it hasn't raised anything
+        let seen_until = pending.get(&session.id).map_or(now, |gap| gap.last_seen_at);
+        *self.budget_alerts.lock().unwrap() = BudgetAlerts::raised_by(&session, seen_until);
+        *current = Some(session);
+        *self.others.lock().unwrap() = timers;
         Ok(())
     }
 
-    pub fn start_session(&self) -> Result<Session> {
+    /// Start a new session on the primary timer, optionally time-boxed to `budget`
+    pub fn start_session(
+        &self,
+        project_id: Option<String>,
+        budget: Option<SessionBudget>,
+    ) -> Result<Session> {
         let mut current = self.current_session.lock().unwrap();
 
         if current.is_some() {
-            anyhow::bail!("A session is already active");
+            fail!(AlreadyActive, "A session is already active");
+        }
+
+        self.begin_session(&mut current, DEFAULT_TIMER_NAME, project_id, budget)
+    }
+
+    /// Start a session on the timer called `name`. It becomes the primary
+    /// timer if none is running, and otherwise runs alongside it.
+    pub fn start_named_session(
+        &self,
+        name: &str,
+        project_id: Option<String>,
+        budget: Option<SessionBudget>,
+    ) -> Result<Session> {
+        let name = timer_name(name)?;
+        let mut current = self.current_session.lock().unwrap();
+
+        let Some(primary) = current.as_ref() else {
+            return self.begin_session(&mut current, &name, project_id, budget);
+        };
+        if primary.timer_name == name {
+            fail!(AlreadyActive, "The {} timer is already running", name);
+        }
+        if budget.is_some() {
+            fail!(Validation, "Budgets only apply to the primary timer");
         }
 
-        let session = Session::new();
+        let mut others = self.others.lock().unwrap();
+        if others.contains_key(&name) {
+            fail!(AlreadyActive, "The {} timer is already running", name);
+        }
+        let session = self.new_session(&name, project_id, None)?;
+        others.insert(name, session.clone());
+        self.publish(TimerEvent::Started { session: session.clone() });
+
+        Ok(session)
+    }
+
+    /// Create and save a session on the timer called `name`
+    fn new_session(
+        &self,
+        name: &str,
+        project_id: Option<String>,
+        budget: Option<SessionBudget>,
+    ) -> Result<Session> {
+        if let Some(id) = project_id.as_deref() {
+            if self.store.get_project(id)?.is_none() {
+                fail!(NotFound, "Project not found");
+            }
+        }
+        if let Some(budget) = &budget {
+            budget.validate()?;
+        }
+
+        let mut session = Session::new(project_id);
+        session.timer_name = name.to_string();
+        session.budget = budget;
         self.store.save_session(&session)?;
+
+        Ok(session)
+    }
+
+    /// Start a session on the primary timer, which must be free
+    fn begin_session(
+        &self,
+        current: &mut Option<Session>,
+        name: &str,
+        project_id: Option<String>,
+        budget: Option<SessionBudget>,
+    ) -> Result<Session> {
+        if self.others.lock().unwrap().contains_key(name) {
+            fail!(
+                AlreadyActive,
+                "The {} timer is already running alongside; make it the primary timer instead",
+                name
+            );
+        }
+
+        let session = self.new_session(name, project_id, budget)?;
         *current = Some(session.clone());
+        *self.budget_alerts.lock().unwrap() = BudgetAlerts::default();
+        self.store.save_primary_timer(name)?;
+        self.publish(TimerEvent::Started { session: session.clone() });
 
         Ok(session)
     }
 
-    pub fn pause_session(&self) -> Result<Session> {
+    /// Pause the primary timer, recording why (e.g. "Lunch") if given
+    pub fn pause_session(&self, reason: Option<String>) -> Result<Session> {
         let mut current = self.current_session.lock().unwrap();
+        self.pause_primary(&mut current, reason)
+    }
 
+    fn pause_primary(&self, current: &mut Option<Session>, reason: Option<String>) -> Result<Session> {
         let session = current
             .as_mut()
-            .context("No active session to pause")?;
+            .or_error(TimelyError::NotFound, "No active session to pause")?;
 
-        if session.is_paused() {
-            anyhow::bail!("Session is already paused");
-        }
-
-        session.pauses.push(Utc::now());
+        pause(session, reason)?;
         self.store.save_session(session)?;
+        // Pausing by hand takes the session out of Pomodoro mode
+        self.pomodoro.lock().unwrap().take();
+        self.publish(TimerEvent::Paused { session: session.clone() });
 
         Ok(session.clone())
     }
 
     pub fn resume_session(&self) -> Result<Session> {
         let mut current = self.current_session.lock().unwrap();
+        self.resume_primary(&mut current)
+    }
 
+    fn resume_primary(&self, current: &mut Option<Session>) -> Result<Session> {
         let session = current
             .as_mut()
-            .context("No active session to resume")?;
+            .or_error(TimelyError::NotFound, "No active session to resume")?;
 
-        if !session.is_paused() {
-            anyhow::bail!("Session is not paused");
+        resume(session)?;
+        self.store.save_session(session)?;
+        // Resuming by hand, e.g. skipping a Pomodoro break, leaves Pomodoro mode
+        self.pomodoro.lock().unwrap().take();
+        self.publish(TimerEvent::Resumed { session: session.clone() });
+
+        Ok(session.clone())
+    }
+
+    pub fn end_session(&self) -> Result<Session> {
+        let mut current = self.current_session.lock().unwrap();
+        self.finish_session(&mut current)
+    }
+
+    /// Pause the timer called `name`, primary or not
+    pub fn pause_named_session(&self, name: &str, reason: Option<String>) -> Result<Session> {
+        let mut current = self.current_session.lock().unwrap();
+        if is_named(&current, name) {
+            return self.pause_primary(&mut current, reason);
         }
+        self.modify_other(name, |session| pause(session, reason))
+    }
 
-        session.resumes.push(Utc::now());
+    /// Resume the timer called `name`, primary or not
+    pub fn resume_named_session(&self, name: &str) -> Result<Session> {
+        let mut current = self.current_session.lock().unwrap();
+        if is_named(&current, name) {
+            return self.resume_primary(&mut current);
+        }
+        self.modify_other(name, resume)
+    }
+
+    /// End the session on the timer called `name`, primary or not. Ending the
+    /// primary leaves the other timers running without one until another is
+    /// started or made primary.
+    pub fn end_named_session(&self, name: &str) -> Result<Session> {
+        let mut current = self.current_session.lock().unwrap();
+        if is_named(&current, name) {
+            return self.finish_session(&mut current);
+        }
+
+        let mut session = self
+            .others
+            .lock()
+            .unwrap()
+            .remove(name)
+            .ok_or_else(|| no_such_timer(name))?;
+        close(&mut session);
+        self.store.save_session(&session)?;
+        self.pending_gaps.lock().unwrap().remove(&session.id);
+        self.publish(TimerEvent::Ended { session: session.clone() });
+
+        Ok(session)
+    }
+
+    fn modify_other(
+        &self,
+        name: &str,
+        change: impl FnOnce(&mut Session) -> Result<()>,
+    ) -> Result<Session> {
+        let mut others = self.others.lock().unwrap();
+        let session = others
+            .get_mut(name)
+            .ok_or_else(|| no_such_timer(name))?;
+
+        change(session)?;
         self.store.save_session(session)?;
+        self.publish(TimerEvent::pause_change(session));
 
         Ok(session.clone())
     }
 
-    pub fn end_session(&self) -> Result<Session> {
+    /// Make the timer called `name` the primary one, moving the current
+    /// primary alongside. Pomodoro and budget tracking start afresh.
+    pub fn set_primary_timer(&self, name: &str) -> Result<()> {
         let mut current = self.current_session.lock().unwrap();
+        if is_named(&current, name) {
+            return Ok(());
+        }
+        if self.pending_idle.lock().unwrap().is_some()
+            || !self.pending_gaps.lock().unwrap().is_empty()
+        {
+            fail!(
+                Conflict,
+                "Decide what to do with the time away before switching timers"
+            );
+        }
+
+        let mut others = self.others.lock().unwrap();
+        let promoted = others
+            .remove(name)
+            .ok_or_else(|| no_such_timer(name))?;
+        if let Some(previous) = current.take() {
+            others.insert(previous.timer_name.clone(), previous);
+        }
+        *current = Some(promoted);
 
+        self.pomodoro.lock().unwrap().take();
+        self.lock_break_id.lock().unwrap().take();
+        *self.budget_alerts.lock().unwrap() = BudgetAlerts::default();
+        self.store.save_primary_timer(name)?;
+        self.publish(TimerEvent::Changed);
+
+        Ok(())
+    }
+
+    fn finish_session(&self, current: &mut Option<Session>) -> Result<Session> {
         let mut session = current
             .take()
-            .context("No active session to end")?;
+            .or_error(TimelyError::NotFound, "No active session to end")?;
+
+        close(&mut session);
+        self.store.save_session(&session)?;
+        self.pending_idle.lock().unwrap().take();
+        self.pending_gaps.lock().unwrap().remove(&session.id);
+        self.pomodoro.lock().unwrap().take();
+        self.publish(TimerEvent::Ended { session: session.clone() });
+
+        Ok(session)
+    }
+
+    /// Raise the running session's budget warning and expiry, each once, and
+    /// apply the budget's expiry action when it runs out
+    pub fn check_budget(&self) -> Result<Option<BudgetEvent>> {
+        let mut current = self.current_session.lock().unwrap();
+        let mut alerts = self.budget_alerts.lock().unwrap();
+
+        let Some(session) = current.as_mut() else {
+            return Ok(None);
+        };
+        let Some(budget) = session.budget.clone() else {
+            return Ok(None);
+        };
+        if alerts.expired {
+            return Ok(None);
+        }
+
+        let elapsed_seconds = session.calculate_total_seconds();
+        let mut event = BudgetEvent {
+            kind: BudgetEventKind::Warning,
+            session_id: session.id.clone(),
+            budget_seconds: budget.seconds,
+            elapsed_seconds,
+            action: budget.on_expiry,
+        };
+
+        if elapsed_seconds >= budget.seconds {
+            alerts.expired = true;
+            alerts.warned = true;
+            event.kind = BudgetEventKind::Expired;
+
+            match budget.on_expiry {
+                BudgetExpiryAction::Notify => {}
+                BudgetExpiryAction::Pause if !session.is_paused() => {
+                    let mut budget_break = Break::new(&session.id, Utc::now());
+                    budget_break.reason = Some(BUDGET_BREAK_REASON.to_string());
+                    session.breaks.push(budget_break);
+                    self.store.save_session(session)?;
+                    self.pomodoro.lock().unwrap().take();
+                    self.publish(TimerEvent::Paused { session: session.clone() });
+                }
+                BudgetExpiryAction::Pause => {}
+                BudgetExpiryAction::End => {
+                    self.finish_session(&mut current)?;
+                }
+            }
+            return Ok(Some(event));
+        }
+
+        if !alerts.warned && elapsed_seconds >= budget.seconds - budget.warning_seconds {
+            alerts.warned = true;
+            return Ok(Some(event));
+        }
+
+        Ok(None)
+    }
+
+    /// Run `check_budget` every `every` for the lifetime of the app, handing
+    /// each warning or expiry to `on_event`
+    pub async fn run_budget_checks(
+        self,
+        every: std::time::Duration,
+        on_event: impl Fn(BudgetEvent) + Send + 'static,
+    ) {
+        run_every(every, || self.check_budget(), on_event, "Budget check").await
+    }
+
+    /// Start Pomodoro mode with the saved phase lengths, on the running
+    /// session or on a new one if none is running
+    pub fn start_pomodoro(&self, project_id: Option<String>) -> Result<PomodoroState> {
+        let settings = self.store.get_pomodoro_settings()?;
+        let mut current = self.current_session.lock().unwrap();
+        let mut pomodoro = self.pomodoro.lock().unwrap();
+
+        if pomodoro.is_some() {
+            fail!(AlreadyActive, "A Pomodoro is already running");
+        }
+        match current.as_ref() {
+            Some(session) if session.is_paused() => {
+                fail!(Conflict, "Resume the session before starting a Pomodoro")
+            }
+            Some(_) => {}
+            None => {
+                self.begin_session(&mut current, DEFAULT_TIMER_NAME, project_id, None)?;
+            }
+        }
+
+        let now = Utc::now();
+        let state = PomodoroState {
+            phase: PomodoroPhase::Focus,
+            cycle: 1,
+            phase_started_at: now,
+            phase_ends_at: now + settings.length_of(PomodoroPhase::Focus),
+            settings,
+        };
+        *pomodoro = Some(state.clone());
+        self.publish(TimerEvent::Changed);
+
+        Ok(state)
+    }
+
+    /// Leave Pomodoro mode. The session keeps running; if it was on a
+    /// Pomodoro break, the break ends now.
+    pub fn stop_pomodoro(&self) -> Result<()> {
+        let mut current = self.current_session.lock().unwrap();
+        let state = self
+            .pomodoro
+            .lock()
+            .unwrap()
+            .take()
+            .or_error(TimelyError::NotFound, "No Pomodoro is running")?;
+
+        if let Some(session) = current.as_mut().filter(|_| state.phase.is_break()) {
+            if let Some(pomodoro_break) = session.breaks.last_mut().filter(|b| b.is_open()) {
+                pomodoro_break.ended_at = Some(Utc::now());
+                self.store.save_session(session)?;
+            }
+        }
+        self.publish(TimerEvent::Changed);
+
+        Ok(())
+    }
+
+    pub fn get_pomodoro(&self) -> Option<PomodoroState> {
+        self.pomodoro.lock().unwrap().clone()
+    }
+
+    /// Move a running Pomodoro on once its phase is over, recording breaks on
+    /// the session at the exact phase boundaries
+    pub fn advance_pomodoro(&self) -> Result<Option<PomodoroTransition>> {
+        let mut current = self.current_session.lock().unwrap();
+        let mut pomodoro = self.pomodoro.lock().unwrap();
+        let now = Utc::now();
+
+        let (Some(session), Some(state)) = (current.as_mut(), pomodoro.as_mut()) else {
+            return Ok(None);
+        };
+        if now < state.phase_ends_at {
+            return Ok(None);
+        }
+
+        let from = state.phase;
+        // Catch up phase by phase in case several went by, e.g. while asleep
+        while state.phase_ends_at <= now {
+            let at = state.phase_ends_at;
+            let next = match state.phase {
+                PomodoroPhase::Focus if state.cycle >= state.settings.cycles_before_long_break => {
+                    PomodoroPhase::LongBreak
+                }
+                PomodoroPhase::Focus => PomodoroPhase::ShortBreak,
+                PomodoroPhase::ShortBreak | PomodoroPhase::LongBreak => PomodoroPhase::Focus,
+            };
+
+            if next.is_break() {
+                let mut pomodoro_break = Break::new(&session.id, at);
+                pomodoro_break.reason = Some(format!("Pomodoro {}", next.label().to_lowercase()));
+                session.breaks.push(pomodoro_break);
+            } else {
+                if let Some(pomodoro_break) = session.breaks.last_mut().filter(|b| b.is_open()) {
+                    pomodoro_break.ended_at = Some(at);
+                }
+                state.cycle = match state.phase {
+                    PomodoroPhase::LongBreak => 1,
+                    _ => state.cycle + 1,
+                };
+            }
+
+            state.phase = next;
+            state.phase_started_at = at;
+            state.phase_ends_at = at + state.settings.length_of(next);
+        }
+
+        self.store.save_session(session)?;
+        self.publish(TimerEvent::pause_change(session));
+
+        Ok(Some(PomodoroTransition {
+            from,
+            state: state.clone(),
+        }))
+    }
+
+    /// Run `advance_pomodoro` every `every` for the lifetime of the app,
+    /// handing each phase change to `on_transition`
+    pub async fn run_pomodoro(
+        self,
+        every: std::time::Duration,
+        on_transition: impl Fn(PomodoroTransition) + Send + 'static,
+    ) {
+        run_every(every, || self.advance_pomodoro(), on_transition, "Pomodoro update").await
+    }
+
+    /// Persist that the running sessions are still alive, along with their
+    /// totals so far, so a crash loses at most one interval. Returns the gaps
+    /// of sessions whose previous heartbeat is too long ago, e.g. because the
+    /// machine slept.
+    pub fn heartbeat(&self) -> Result<Vec<SessionGap>> {
+        let mut current = self.current_session.lock().unwrap();
+        let mut pending = self.pending_gaps.lock().unwrap();
+        let mut others = self.others.lock().unwrap();
+        let now = Utc::now();
+
+        let mut gaps = Vec::new();
+        for session in current.iter_mut().chain(others.values_mut()) {
+            // A gap awaiting a decision isn't reported twice, and its session
+            // keeps its last heartbeat so the gap is found again after a restart
+            if pending.contains_key(&session.id) {
+                continue;
+            }
+            if let Some(gap) = detect_gap(session, now) {
+                pending.insert(session.id.clone(), gap.clone());
+                gaps.push(gap);
+                continue;
+            }
+
+            session.last_seen_at = Some(now);
+            session.total_seconds = session.calculate_total_seconds();
+            self.store
+                .record_heartbeat(&session.id, now, session.total_seconds)?;
+        }
+
+        Ok(gaps)
+    }
+
+    /// Run `heartbeat` every `every` for the lifetime of the app, handing each
+    /// gap it notices to `on_gap`. Meant to be spawned as a background task.
+    pub async fn run_heartbeat(
+        self,
+        every: std::time::Duration,
+        on_gap: impl Fn(SessionGap) + Send + 'static,
+    ) {
+        run_every(
+            every,
+            || Ok(Some(self.heartbeat()?).filter(|gaps| !gaps.is_empty())),
+            |gaps| gaps.into_iter().for_each(&on_gap),
+            "Heartbeat",
+        )
+        .await
+    }
+
+    /// Gaps awaiting the user's decision, earliest first
+    pub fn get_pending_gaps(&self) -> Vec<SessionGap> {
+        let mut gaps: Vec<SessionGap> = self
+            .pending_gaps
+            .lock()
+            .unwrap()
+            .values()
+            .cloned()
+            .collect();
+        gaps.sort_by_key(|gap| gap.last_seen_at);
+        gaps
+    }
+
+    /// Settle the shutdown or sleep gap pending on the session `session_id`,
+    /// on any timer
+    pub fn resolve_gap(&self, session_id: &str, resolution: GapResolution) -> Result<Session> {
+        let mut current = self.current_session.lock().unwrap();
+        let mut pending = self.pending_gaps.lock().unwrap();
+        let mut others = self.others.lock().unwrap();
+
+        let gap = pending.get(session_id).cloned().or_error(
+            TimelyError::NotFound,
+            "No gap is awaiting a decision for this session",
+        )?;
+        let primary = current.as_ref().is_some_and(|s| s.id == session_id);
+        let session = match current.as_mut().filter(|_| primary) {
+            Some(session) => session,
+            None => others
+                .values_mut()
+                .find(|s| s.id == session_id)
+                .or_error(TimelyError::Conflict, "The session is no longer running")?,
+        };
+
+        match resolution {
+            GapResolution::EndAtLastSeen => end_at(session, gap.last_seen_at),
+            GapResolution::InsertBreak => {
+                let mut away = Break::new(&session.id, gap.last_seen_at);
+                away.ended_at = Some(gap.detected_at);
+                away.reason = Some(GAP_BREAK_REASON.to_string());
+                session.breaks.push(away);
+            }
+            GapResolution::Keep => {}
+        }
+        if session.end.is_none() {
+            session.last_seen_at = Some(Utc::now());
+        }
+
+        self.store.save_session(session)?;
+        let session = session.clone();
+        pending.remove(session_id);
+        if primary {
+            self.pomodoro.lock().unwrap().take();
+        }
+        if session.end.is_some() {
+            if primary {
+                *current = None;
+                self.pending_idle.lock().unwrap().take();
+            } else {
+                others.remove(&session.timer_name);
+            }
+            self.publish(TimerEvent::Ended { session: session.clone() });
+        } else {
+            self.publish(TimerEvent::Changed);
+        }
+
+        Ok(session)
+    }
+
+    /// React to the user having been idle for `idle_for`. Pauses the running
+    /// session once the idle threshold is crossed, backdating the break to
+    /// when input stopped, and reports when the user comes back.
+    pub fn check_idle(&self, idle_for: Duration, settings: &IdleSettings) -> Result<Option<IdleEvent>> {
+        let mut current = self.current_session.lock().unwrap();
+        let mut pending = self.pending_idle.lock().unwrap();
+        let now = Utc::now();
+
+        let Some(session) = current.as_mut() else {
+            return Ok(None);
+        };
+        // Time away while asleep or shut down is settled through the gap instead
+        if self.pending_gaps.lock().unwrap().contains_key(&session.id) {
+            return Ok(None);
+        }
+
+        match pending.as_mut() {
+            None => {
+                if !settings.enabled || idle_for < settings.threshold() || session.is_paused() {
+                    return Ok(None);
+                }
+
+                // The break can't reach back past the session start or an earlier break
+                let last_active = session
+                    .breaks
+                    .last()
+                    .and_then(|b| b.ended_at)
+                    .unwrap_or(session.start);
+                let started_at = (now - idle_for).max(last_active);
+
+                let mut idle_break = Break::new(&session.id, started_at);
+                idle_break.reason = Some(IDLE_BREAK_REASON.to_string());
+                let interval = IdleInterval {
+                    session_id: session.id.clone(),
+                    break_id: idle_break.id.clone(),
+                    started_at,
+                    returned_at: None,
+                };
+                session.breaks.push(idle_break);
+                self.store.save_session(session)?;
+                self.pomodoro.lock().unwrap().take();
+                self.publish(TimerEvent::Paused { session: session.clone() });
+
+                *pending = Some(interval.clone());
+                Ok(Some(IdleEvent::AutoPaused(interval)))
+            }
+            Some(interval) if interval.returned_at.is_none() && idle_for < settings.threshold() => {
+                interval.returned_at = Some((now - idle_for).max(interval.started_at));
+                Ok(Some(IdleEvent::Returned(interval.clone())))
+            }
+            Some(_) => Ok(None),
+        }
+    }
+
+    /// Pause the running session because the screen locked. Sessions already
+    /// paused, by hand or otherwise, are left as they are.
+    pub fn screen_locked(&self) -> Result<Option<Session>> {
+        let mut current = self.current_session.lock().unwrap();
+        let mut lock_break_id = self.lock_break_id.lock().unwrap();
+
+        let Some(session) = current.as_mut().filter(|s| !s.is_paused()) else {
+            return Ok(None);
+        };
+
+        let mut lock_break = Break::new(&session.id, Utc::now());
+        lock_break.reason = Some(LOCK_BREAK_REASON.to_string());
+        *lock_break_id = Some(lock_break.id.clone());
+        session.breaks.push(lock_break);
+        self.store.save_session(session)?;
+        self.pomodoro.lock().unwrap().take();
+        self.publish(TimerEvent::Paused { session: session.clone() });
+
+        Ok(Some(session.clone()))
+    }
+
+    /// Resume the session the screen lock paused. If the user has since
+    /// resumed, ended or otherwise moved on, there's nothing to do.
+    pub fn screen_unlocked(&self) -> Result<Option<Session>> {
+        let mut current = self.current_session.lock().unwrap();
+        let Some(break_id) = self.lock_break_id.lock().unwrap().take() else {
+            return Ok(None);
+        };
+
+        let Some(session) = current.as_mut() else {
+            return Ok(None);
+        };
+        let Some(lock_break) = session
+            .breaks
+            .last_mut()
+            .filter(|b| b.id == break_id && b.is_open())
+        else {
+            return Ok(None);
+        };
+
+        lock_break.ended_at = Some(Utc::now());
+        self.store.save_session(session)?;
+        self.publish(TimerEvent::Resumed { session: session.clone() });
+
+        Ok(Some(session.clone()))
+    }
+
+    pub fn get_pending_idle(&self) -> Option<IdleInterval> {
+        self.pending_idle.lock().unwrap().clone()
+    }
+
+    /// Settle the pending idle interval and carry on timing
+    pub fn resolve_idle(&self, resolution: IdleResolution) -> Result<Session> {
+        let mut current = self.current_session.lock().unwrap();
+        let mut pending = self.pending_idle.lock().unwrap();
+
+        let interval = pending
+            .as_ref()
+            .or_error(TimelyError::NotFound, "No idle time is awaiting a decision")?;
+        let session = current
+            .as_mut()
+            .filter(|s| s.id == interval.session_id)
+            .or_error(
+                TimelyError::Conflict,
+                "The idle session is no longer running",
+            )?;
+        let position = session
+            .breaks
+            .iter()
+            .position(|b| b.id == interval.break_id)
+            .or_error(TimelyError::NotFound, "Idle break not found")?;
+        let returned_at = interval.returned_at.unwrap_or_else(Utc::now);
+
+        match resolution {
+            IdleResolution::Keep => {
+                session.breaks.remove(position);
+            }
+            IdleResolution::Discard => {
+                let idle_break = &mut session.breaks[position];
+                idle_break.ended_at.get_or_insert(returned_at);
+            }
+            IdleResolution::Reassign { reason } => {
+                let reason = reason.trim();
+                if reason.is_empty() {
+                    fail!(Validation, "A reason is required to reassign idle time");
+                }
+                let idle_break = &mut session.breaks[position];
+                idle_break.ended_at.get_or_insert(returned_at);
+                idle_break.reason = Some(reason.to_string());
+            }
+        }
+
+        self.store.save_session(session)?;
+        *pending = None;
+        self.publish(TimerEvent::pause_change(session));
+
+        Ok(session.clone())
+    }
+
+    pub fn set_session_description(
+        &self,
+        session_id: &str,
+        description: Option<String>,
+    ) -> Result<Session> {
+        let description = description
+            .map(|d| d.trim().to_string())
+            .filter(|d| !d.is_empty());
+        self.modify_session(session_id, |session| session.description = description)
+    }
+
+    pub fn set_session_tags(&self, session_id: &str, tags: Vec<String>) -> Result<Session> {
+        let tags = normalize_tags(&tags);
+        self.modify_session(session_id, |session| session.tags = tags)
+    }
 
-        session.end = Some(Utc::now());
-        session.total_seconds = session.calculate_total_seconds();
+    /// Apply a change to a session, going through the in-memory copy when it is
+    /// running on any timer so that the next pause/resume doesn't overwrite it.
+    fn modify_session(&self, session_id: &str, change: impl FnOnce(&mut Session)) -> Result<Session> {
+        let mut current = self.current_session.lock().unwrap();
+        let mut others = self.others.lock().unwrap();
+
+        let running = current
+            .iter_mut()
+            .chain(others.values_mut())
+            .find(|s| s.id == session_id);
+        if let Some(session) = running {
+            change(session);
+            self.store.save_session(session)?;
+            return Ok(session.clone());
+        }
+
+        let mut session = self
+            .store
+            .get_session(session_id)?
+            .or_error(TimelyError::NotFound, "Session not found")?;
+        change(&mut session);
         self.store.save_session(&session)?;
 
         Ok(session)
@@ -97,6 +924,66 @@ impl TimerManager {
         self.current_session.lock().unwrap().clone()
     }
 
+    pub fn get_state(&self) -> TimerState {
+        let current = self.current_session.lock().unwrap();
+        let timers = current
+            .iter()
+            .map(|session| RunningTimer::of(session, true))
+            .chain(
+                self.others
+                    .lock()
+                    .unwrap()
+                    .values()
+                    .map(|session| RunningTimer::of(session, false)),
+            )
+            .collect();
+
+        match current.as_ref() {
+            Some(session) => TimerState {
+                is_running: true,
+                is_paused: session.is_paused(),
+                current_session_id: Some(session.id.clone()),
+                elapsed_seconds: session.calculate_total_seconds(),
+                pomodoro: self.get_pomodoro(),
+                timers,
+            },
+            None => TimerState {
+                timers,
+                ..TimerState::default()
+            },
+        }
+    }
+
+    /// Every running timer, the primary first
+    pub fn list_timers(&self) -> Vec<RunningTimer> {
+        self.get_state().timers
+    }
+
+    /// Be told whenever a timer starts, pauses, resumes or ends, including
+    /// changes made by the idle, gap, Pomodoro and budget handling, and get a
+    /// `Tick` every `EVENT_TICK` while `run_ticks` is running. Read `get_state`
+    /// for the state after other events, or after falling behind.
+    pub fn subscribe(&self) -> broadcast::Receiver<TimerEvent> {
+        self.events.subscribe()
+    }
+
+    fn publish(&self, event: TimerEvent) {
+        // Nobody listening is fine
+        let _ = self.events.send(event);
+    }
+
+    /// Publish a `Tick` with the current state every `every` while any timer
+    /// is running, for the lifetime of the app
+    pub async fn run_ticks(self, every: std::time::Duration) {
+        run_every(
+            every,
+            || Ok(Some(self.get_state()).filter(|state| !state.timers.is_empty())),
+            |state| self.publish(TimerEvent::Tick { state }),
+            "Tick",
+        )
+        .await
+    }
+
     pub fn get_current_elapsed_seconds(&self) -> i64 {
         let current = self.current_session.lock().unwrap();
         if let Some(session) = current.as_ref() {
@@ -106,3 +993,770 @@ impl TimerManager {
         }
     }
 }
+
+/// Call `step` every `every` forever, passing whatever it produces to `on_output`.
+/// Errors are logged and the loop carries on.
+async fn run_every<T>(
+    every: std::time::Duration,
+    step: impl Fn() -> Result<Option<T>>,
+    on_output: impl Fn(T),
+    what: &str,
+) {
+    let mut ticker = tokio::time::interval(every);
+    // After a sleep, carry on from now rather than firing the missed ticks
+    ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+    loop {
+        ticker.tick().await;
+        match step() {
+            Ok(Some(output)) => on_output(output),
+            Ok(None) => {}
+            Err(e) => eprintln!("{} failed: {}", what, e),
+        }
+    }
+}
+
+/// Whether the primary timer is the one called `name`
+fn is_named(current: &Option<Session>, name: &str) -> bool {
+    current.as_ref().is_some_and(|s| s.timer_name == name)
+}
+
+fn no_such_timer(name: &str) -> TimelyError {
+    TimelyError::NotFound(format!("No timer called {} is running", name))
+}
+
+fn timer_name(name: &str) -> Result<String> {
+    let name = name.trim();
+    if name.is_empty() {
+        fail!(Validation, "A timer needs a name");
+    }
+    if name.chars().count() > 50 {
+        fail!(Validation, "Timer names can be at most 50 characters");
+    }
+    Ok(name.to_string())
+}
+
+fn pause(session: &mut Session, reason: Option<String>) -> Result<()> {
+    if session.is_paused() {
+        fail!(AlreadyPaused, "Session is already paused");
+    }
+
+    let mut pause = Break::new(&session.id, Utc::now());
+    pause.reason = reason
+        .map(|r| r.trim().to_string())
+        .filter(|r| !r.is_empty());
+    session.breaks.push(pause);
+    Ok(())
+}
+
+fn resume(session: &mut Session) -> Result<()> {
+    if !session.is_paused() {
+        fail!(NotPaused, "Session is not paused");
+    }
+
+    if let Some(current_break) = session.breaks.last_mut() {
+        current_break.ended_at = Some(Utc::now());
+    }
+    Ok(())
+}
+
+/// End a session now, along with any break still open
+fn close(session: &mut Session) {
+    let now = Utc::now();
+    if let Some(open_break) = session.breaks.last_mut().filter(|b| b.is_open()) {
+        open_break.ended_at = Some(now);
+    }
+    session.end = Some(now);
+    session.total_seconds = session.calculate_total_seconds();
+}
+
+/// End a session at `at`, before now, cutting its breaks short to fit
+fn end_at(session: &mut Session, at: DateTime<Utc>) {
+    session.breaks.retain(|b| b.started_at < at);
+    for b in &mut session.breaks {
+        if b.ended_at.is_none_or(|ended_at| ended_at > at) {
+            b.ended_at = Some(at);
+        }
+    }
+    session.end = Some(at);
+    session.total_seconds = session.calculate_total_seconds();
+}
+
+/// The last instant a session is known to have been running: its last
+/// heartbeat, or for sessions from before heartbeats were recorded, the latest
+/// change to its breaks or else its start
+fn last_seen(session: &Session) -> DateTime<Utc> {
+    session.last_seen_at.unwrap_or_else(|| {
+        session
+            .breaks
+            .iter()
+            .map(|b| b.ended_at.unwrap_or(b.started_at))
+            .fold(session.start, DateTime::max)
+    })
+}
+
+/// A gap in a running session's heartbeats up to `now`. Paused sessions are
+/// already not accruing time, so they never have one.
+fn detect_gap(session: &Session, now: DateTime<Utc>) -> Option<SessionGap> {
+    let last_seen_at = last_seen(session);
+    if session.is_paused() || now - last_seen_at <= gap_threshold() {
+        return None;
+    }
+
+    Some(SessionGap {
+        session_id: session.id.clone(),
+        timer_name: session.timer_name.clone(),
+        last_seen_at,
+        detected_at: now,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Granularity, OverlapMode, PomodoroSettings, SummaryRange};
+    use crate::test_utils::TempDir;
+    use chrono::TimeZone;
+
+    /// A store holding a session that started `hours` ago and was last seen
+    /// `last_seen_hours` ago, as left behind by a previous run
+    fn left_running(dir: &TempDir, hours: i64, last_seen_hours: i64) -> (Arc<SessionStore>, Session) {
+        let store = Arc::new(SessionStore::new(dir.path().join("timer.db")).unwrap());
+        let mut session = Session::new(None);
+        session.start -= Duration::hours(hours);
+        session.last_seen_at = Some(Utc::now() - Duration::hours(last_seen_hours));
+        store.save_session(&session).unwrap();
+        (store, session)
+    }
+
+    #[test]
+    fn recent_heartbeat_recovers_without_a_gap() {
+        let dir = TempDir::new();
+        let (store, session) = left_running(&dir, 2, 0);
+
+        let timer = TimerManager::new(store).unwrap();
+        assert_eq!(timer.get_current_session().unwrap().id, session.id);
+        assert!(timer.get_pending_gaps().is_empty());
+    }
+
+    #[test]
+    fn ending_at_last_heartbeat_drops_the_weekend() {
+        let dir = TempDir::new();
+        let (store, session) = left_running(&dir, 62, 60);
+
+        let timer = TimerManager::new(store.clone()).unwrap();
+        let gap = timer.get_pending_gaps().remove(0);
+        assert_eq!(gap.last_seen_at, session.last_seen_at.unwrap());
+
+        // A heartbeat while the gap is pending doesn't report it twice
+        assert!(timer.heartbeat().unwrap().is_empty());
+
+        let ended = timer
+            .resolve_gap(&session.id, GapResolution::EndAtLastSeen)
+            .unwrap();
+        assert_eq!(ended.end, session.last_seen_at);
+        assert!((ended.total_seconds - 2 * 3600).abs() <= 1);
+        assert!(timer.get_current_session().is_none());
+        assert!(store.get_active_sessions().unwrap().rows.is_empty());
+    }
+
+    #[test]
+    fn gap_can_become_a_break() {
+        let dir = TempDir::new();
+        let (store, session) = left_running(&dir, 3, 2);
+
+        let timer = TimerManager::new(store).unwrap();
+        let session = timer
+            .resolve_gap(&session.id, GapResolution::InsertBreak)
+            .unwrap();
+
+        let away = session.breaks.last().unwrap();
+        assert_eq!(away.reason.as_deref(), Some(GAP_BREAK_REASON));
+        assert!(!session.is_paused());
+        assert!((session.calculate_total_seconds() - 3600).abs() <= 1);
+        assert!(timer.get_pending_gaps().is_empty());
+    }
+
+    #[test]
+    fn heartbeat_persists_a_provisional_total() {
+        let dir = TempDir::new();
+        let (store, session) = left_running(&dir, 1, 0);
+
+        let timer = TimerManager::new(store.clone()).unwrap();
+        assert!(timer.heartbeat().unwrap().is_empty());
+
+        let stored = store.get_session(&session.id).unwrap().unwrap();
+        assert!(stored.end.is_none());
+        assert!((stored.total_seconds - 3600).abs() <= 1);
+        assert!(stored.last_seen_at.unwrap() > session.last_seen_at.unwrap());
+    }
+
+    #[test]
+    fn paused_sessions_have_no_gap() {
+        let dir = TempDir::new();
+        let store = Arc::new(SessionStore::new(dir.path().join("timer.db")).unwrap());
+        let mut session = Session::new(None);
+        session.start -= Duration::hours(10);
+        session.breaks.push(Break::new(&session.id, session.start + Duration::hours(1)));
+        session.last_seen_at = Some(session.start + Duration::hours(1));
+        store.save_session(&session).unwrap();
+
+        let timer = TimerManager::new(store).unwrap();
+        assert!(timer.get_pending_gaps().is_empty());
+    }
+
+    /// Pretend the current Pomodoro phase ended `ago` and started a phase-length earlier
+    fn end_phase(timer: &TimerManager, ago: Duration) {
+        let mut pomodoro = timer.pomodoro.lock().unwrap();
+        let state = pomodoro.as_mut().unwrap();
+        let length = state.settings.length_of(state.phase);
+        state.phase_ends_at = Utc::now() - ago;
+        state.phase_started_at = state.phase_ends_at - length;
+    }
+
+    fn pomodoro_timer(dir: &TempDir) -> TimerManager {
+        let store = Arc::new(SessionStore::new(dir.path().join("timer.db")).unwrap());
+        store
+            .save_pomodoro_settings(&PomodoroSettings {
+                cycles_before_long_break: 2,
+                ..Default::default()
+            })
+            .unwrap();
+        TimerManager::new(store).unwrap()
+    }
+
+    #[test]
+    fn pomodoro_cycles_through_phases_recording_breaks() {
+        let dir = TempDir::new();
+        let timer = pomodoro_timer(&dir);
+        timer.start_pomodoro(None).unwrap();
+        assert!(timer.advance_pomodoro().unwrap().is_none());
+
+        end_phase(&timer, Duration::seconds(1));
+        let transition = timer.advance_pomodoro().unwrap().unwrap();
+        assert_eq!(transition.from, PomodoroPhase::Focus);
+        assert_eq!(transition.state.phase, PomodoroPhase::ShortBreak);
+        let session = timer.get_current_session().unwrap();
+        assert!(session.is_paused());
+        assert_eq!(session.breaks[0].reason.as_deref(), Some("Pomodoro short break"));
+        assert_eq!(session.breaks[0].started_at, transition.state.phase_started_at);
+
+        end_phase(&timer, Duration::seconds(1));
+        let transition = timer.advance_pomodoro().unwrap().unwrap();
+        assert_eq!(transition.state.phase, PomodoroPhase::Focus);
+        assert_eq!(transition.state.cycle, 2);
+        assert!(!timer.get_current_session().unwrap().is_paused());
+
+        end_phase(&timer, Duration::seconds(1));
+        let transition = timer.advance_pomodoro().unwrap().unwrap();
+        assert_eq!(transition.state.phase, PomodoroPhase::LongBreak);
+
+        end_phase(&timer, Duration::seconds(1));
+        let transition = timer.advance_pomodoro().unwrap().unwrap();
+        assert_eq!(transition.state.phase, PomodoroPhase::Focus);
+        assert_eq!(transition.state.cycle, 1);
+    }
+
+    #[test]
+    fn pomodoro_catches_up_on_missed_phases() {
+        let dir = TempDir::new();
+        let timer = pomodoro_timer(&dir);
+        timer.start_pomodoro(None).unwrap();
+        timer.current_session.lock().unwrap().as_mut().unwrap().start -= Duration::hours(1);
+
+        // Focus ended 7 minutes ago: a 5 minute short break and 2 minutes of focus since
+        end_phase(&timer, Duration::minutes(7));
+        let transition = timer.advance_pomodoro().unwrap().unwrap();
+        assert_eq!(transition.state.phase, PomodoroPhase::Focus);
+        assert_eq!(transition.state.cycle, 2);
+
+        let session = timer.get_current_session().unwrap();
+        assert_eq!(session.breaks.len(), 1);
+        assert_eq!(session.break_seconds(), 5 * 60);
+    }
+
+    #[test]
+    fn stopping_pomodoro_on_a_break_resumes_work() {
+        let dir = TempDir::new();
+        let timer = pomodoro_timer(&dir);
+        timer.start_pomodoro(None).unwrap();
+        end_phase(&timer, Duration::seconds(1));
+        timer.advance_pomodoro().unwrap();
+
+        timer.stop_pomodoro().unwrap();
+        assert!(timer.get_pomodoro().is_none());
+        assert!(!timer.get_current_session().unwrap().is_paused());
+        assert!(timer.stop_pomodoro().is_err());
+    }
+
+    #[test]
+    fn pausing_by_hand_leaves_pomodoro_mode() {
+        let dir = TempDir::new();
+        let timer = pomodoro_timer(&dir);
+        timer.start_pomodoro(None).unwrap();
+
+        timer.pause_session(None).unwrap();
+        assert!(timer.get_pomodoro().is_none());
+        assert!(timer.start_pomodoro(None).is_err());
+    }
+
+    /// A timer running a session started `minutes` ago with a 60 minute budget
+    fn budgeted(dir: &TempDir, minutes: i64, on_expiry: BudgetExpiryAction) -> TimerManager {
+        let store = Arc::new(SessionStore::new(dir.path().join("timer.db")).unwrap());
+        let timer = TimerManager::new(store).unwrap();
+        let budget = SessionBudget {
+            seconds: 60 * 60,
+            warning_seconds: 10 * 60,
+            on_expiry,
+        };
+        timer.start_session(None, Some(budget)).unwrap();
+        timer.current_session.lock().unwrap().as_mut().unwrap().start -= Duration::minutes(minutes);
+        timer
+    }
+
+    #[test]
+    fn budget_warns_once_before_running_out() {
+        let dir = TempDir::new();
+        let timer = budgeted(&dir, 45, BudgetExpiryAction::Notify);
+        assert!(timer.check_budget().unwrap().is_none());
+
+        timer.current_session.lock().unwrap().as_mut().unwrap().start -= Duration::minutes(10);
+        let event = timer.check_budget().unwrap().unwrap();
+        assert_eq!(event.kind, BudgetEventKind::Warning);
+        assert!(timer.check_budget().unwrap().is_none());
+
+        timer.current_session.lock().unwrap().as_mut().unwrap().start -= Duration::minutes(10);
+        let event = timer.check_budget().unwrap().unwrap();
+        assert_eq!(event.kind, BudgetEventKind::Expired);
+        assert!(timer.check_budget().unwrap().is_none());
+        assert!(!timer.get_current_session().unwrap().is_paused());
+    }
+
+    #[test]
+    fn expired_budget_can_pause_the_session() {
+        let dir = TempDir::new();
+        let timer = budgeted(&dir, 61, BudgetExpiryAction::Pause);
+
+        let event = timer.check_budget().unwrap().unwrap();
+        assert_eq!(event.kind, BudgetEventKind::Expired);
+        let session = timer.get_current_session().unwrap();
+        assert!(session.is_paused());
+        assert_eq!(session.breaks[0].reason.as_deref(), Some(BUDGET_BREAK_REASON));
+    }
+
+    #[test]
+    fn expired_budget_can_end_the_session_keeping_budget_for_reports() {
+        let dir = TempDir::new();
+        let timer = budgeted(&dir, 61, BudgetExpiryAction::End);
+        let session_id = timer.get_current_session().unwrap().id;
+
+        timer.check_budget().unwrap().unwrap();
+        assert!(timer.get_current_session().is_none());
+
+        let stored = timer.store.get_session(&session_id).unwrap().unwrap();
+        assert!(stored.end.is_some());
+        assert_eq!(stored.budget.unwrap().seconds, 60 * 60);
+        assert!(stored.total_seconds >= 61 * 60);
+    }
+
+    #[test]
+    fn rejects_invalid_budgets() {
+        let dir = TempDir::new();
+        let store = Arc::new(SessionStore::new(dir.path().join("timer.db")).unwrap());
+        let timer = TimerManager::new(store).unwrap();
+        let budget = SessionBudget {
+            seconds: 60,
+            warning_seconds: 120,
+            on_expiry: BudgetExpiryAction::Notify,
+        };
+        assert!(timer.start_session(None, Some(budget)).is_err());
+        assert!(timer.get_current_session().is_none());
+    }
+
+    #[test]
+    fn subscribers_hear_about_every_transition() {
+        let dir = TempDir::new();
+        let timer = budgeted(&dir, 0, BudgetExpiryAction::Pause);
+        let mut events = timer.subscribe();
+        assert!(events.try_recv().is_err());
+
+        timer.pause_session(None).unwrap();
+        assert!(matches!(events.try_recv(), Ok(TimerEvent::Paused { session }) if session.is_paused()));
+
+        // Failed actions and quiet checks don't count as changes
+        assert!(matches!(
+            timer.pause_session(None),
+            Err(TimelyError::AlreadyPaused(_))
+        ));
+        timer.check_budget().unwrap();
+        assert!(events.try_recv().is_err());
+
+        timer.resume_session().unwrap();
+        assert!(matches!(events.try_recv(), Ok(TimerEvent::Resumed { .. })));
+        timer.current_session.lock().unwrap().as_mut().unwrap().start -= Duration::minutes(61);
+        timer.check_budget().unwrap().unwrap();
+        assert!(matches!(events.try_recv(), Ok(TimerEvent::Paused { .. })));
+
+        let ended = timer.end_session().unwrap();
+        assert!(matches!(events.try_recv(), Ok(TimerEvent::Ended { session }) if session.id == ended.id));
+        assert!(!timer.get_state().is_running);
+    }
+
+    #[tokio::test]
+    async fn ticks_only_while_a_timer_runs() {
+        let dir = TempDir::new();
+        let (_store, timer) = fresh(&dir);
+        let mut events = timer.subscribe();
+        let tick = std::time::Duration::from_millis(10);
+        tokio::spawn(timer.clone().run_ticks(tick));
+
+        tokio::time::sleep(tick * 5).await;
+        assert!(events.try_recv().is_err());
+
+        timer.start_named_session("Build", None, None).unwrap();
+        assert!(matches!(events.recv().await, Ok(TimerEvent::Started { .. })));
+        match events.recv().await {
+            Ok(TimerEvent::Tick { state }) => assert_eq!(state.timers[0].name, "Build"),
+            other => panic!("Expected a tick, got {:?}", other),
+        }
+    }
+
+    fn fresh(dir: &TempDir) -> (Arc<SessionStore>, TimerManager) {
+        let store = Arc::new(SessionStore::new(dir.path().join("timer.db")).unwrap());
+        let timer = TimerManager::new(store.clone()).unwrap();
+        (store, timer)
+    }
+
+    #[test]
+    fn named_timers_run_alongside_the_primary() {
+        let dir = TempDir::new();
+        let (store, timer) = fresh(&dir);
+
+        let focus = timer.start_named_session("Focus", None, None).unwrap();
+        let build = timer.start_named_session("Build", None, None).unwrap();
+        assert_eq!(timer.get_current_session().unwrap().id, focus.id);
+        assert!(matches!(
+            timer.start_named_session(" Build ", None, None),
+            Err(TimelyError::AlreadyActive(_))
+        ));
+        assert!(matches!(
+            timer.start_session(None, None),
+            Err(TimelyError::AlreadyActive(_))
+        ));
+
+        // Background timers can't be budgeted
+        let budget = SessionBudget {
+            seconds: 60,
+            warning_seconds: 0,
+            on_expiry: BudgetExpiryAction::Notify,
+        };
+        assert!(timer.start_named_session("On call", None, Some(budget)).is_err());
+
+        let paused = timer.pause_named_session("Build", None).unwrap();
+        assert!(paused.is_paused());
+        assert!(!timer.get_current_session().unwrap().is_paused());
+
+        let state = timer.get_state();
+        let names: Vec<_> = state.timers.iter().map(|t| (t.name.as_str(), t.primary)).collect();
+        assert_eq!(names, [("Focus", true), ("Build", false)]);
+        assert!(state.timers[1].is_paused);
+
+        timer.resume_named_session("Build").unwrap();
+        let ended = timer.end_named_session("Build").unwrap();
+        assert_eq!(ended.id, build.id);
+        assert!(store.get_session(&build.id).unwrap().unwrap().end.is_some());
+        assert!(matches!(
+            timer.end_named_session("Build"),
+            Err(TimelyError::NotFound(_))
+        ));
+        assert_eq!(timer.list_timers().len(), 1);
+    }
+
+    #[test]
+    fn primary_timer_can_be_switched_and_survives_a_restart() {
+        let dir = TempDir::new();
+        let (store, timer) = fresh(&dir);
+        timer.start_session(None, None).unwrap();
+        let call = timer.start_named_session("On call", None, None).unwrap();
+
+        timer.set_primary_timer("On call").unwrap();
+        assert_eq!(timer.get_current_session().unwrap().id, call.id);
+        assert!(timer.set_primary_timer("Nothing").is_err());
+
+        let restarted = TimerManager::new(store).unwrap();
+        let timers = restarted.list_timers();
+        assert_eq!(timers.len(), 2);
+        assert_eq!(timers[0].name, "On call");
+        assert!(timers[0].primary);
+        assert_eq!(timers[1].name, DEFAULT_TIMER_NAME);
+
+        // Ending the primary leaves the other running without one
+        restarted.end_session().unwrap();
+        assert!(restarted.get_current_session().is_none());
+        assert_eq!(restarted.list_timers().len(), 1);
+        assert!(restarted.start_session(None, None).is_err());
+        restarted.set_primary_timer(DEFAULT_TIMER_NAME).unwrap();
+        assert!(restarted.get_state().is_running);
+    }
+
+    #[test]
+    fn summaries_count_overlapping_timers_once_or_per_timer() {
+        let dir = TempDir::new();
+        let (store, _timer) = fresh(&dir);
+        store.save_timezone("UTC").unwrap();
+
+        let at = |hour| Utc.with_ymd_and_hms(2025, 3, 10, hour, 0, 0).unwrap();
+        let add = |name: &str, start, end| {
+            let mut session = Session::new(None);
+            session.timer_name = name.to_string();
+            session.start = at(start);
+            session.end = Some(at(end));
+            store.create_manual_session(session)
+        };
+        add("Main", 9, 12).unwrap();
+        add("Build", 11, 13).unwrap();
+        // Only sessions on the same timer may overlap
+        assert!(add("Build", 12, 14).is_err());
+
+        let range = SummaryRange::Day {
+            date: at(0).date_naive(),
+        };
+        let per_timer = store
+            .get_summary(&range, Granularity::Day, OverlapMode::PerTimer)
+            .unwrap();
+        assert_eq!(per_timer.total_seconds, 5 * 3600);
+        let once = store
+            .get_summary(&range, Granularity::Day, OverlapMode::Once)
+            .unwrap();
+        assert_eq!(once.total_seconds, 4 * 3600);
+
+        let timers: Vec<_> = once
+            .timer_breakdown
+            .iter()
+            .map(|t| (t.timer_name.as_str(), t.total_seconds))
+            .collect();
+        // The shared hour goes to the session that started first
+        assert_eq!(timers, [("Main", 3 * 3600), ("Build", 3600)]);
+    }
+
+    #[test]
+    fn sessions_without_a_heartbeat_gap_from_their_last_break() {
+        let dir = TempDir::new();
+        let store = Arc::new(SessionStore::new(dir.path().join("timer.db")).unwrap());
+        let mut session = Session::new(None);
+        session.start -= Duration::hours(50);
+        let mut lunch = Break::new(&session.id, session.start + Duration::hours(3));
+        lunch.ended_at = Some(session.start + Duration::hours(4));
+        session.breaks.push(lunch);
+        store.save_session(&session).unwrap();
+
+        let timer = TimerManager::new(store).unwrap();
+        let gap = timer.get_pending_gaps().remove(0);
+        assert_eq!(gap.last_seen_at, session.start + Duration::hours(4));
+
+        let ended = timer
+            .resolve_gap(&session.id, GapResolution::EndAtLastSeen)
+            .unwrap();
+        assert!((ended.total_seconds - 3 * 3600).abs() <= 1);
+    }
+
+    #[test]
+    fn sessions_without_a_heartbeat_or_breaks_gap_from_their_start() {
+        let dir = TempDir::new();
+        let store = Arc::new(SessionStore::new(dir.path().join("timer.db")).unwrap());
+        let mut session = Session::new(None);
+        session.start -= Duration::hours(50);
+        store.save_session(&session).unwrap();
+
+        let timer = TimerManager::new(store).unwrap();
+        assert_eq!(timer.get_pending_gaps()[0].last_seen_at, session.start);
+    }
+
+    /// A store holding a budgeted session started `minutes` ago and seen just
+    /// now, as left behind by a previous run
+    fn budgeted_left_running(dir: &TempDir, minutes: i64) -> Arc<SessionStore> {
+        let store = Arc::new(SessionStore::new(dir.path().join("timer.db")).unwrap());
+        let mut session = Session::new(None);
+        session.start -= Duration::minutes(minutes);
+        session.last_seen_at = Some(Utc::now());
+        session.budget = Some(SessionBudget {
+            seconds: 60 * 60,
+            warning_seconds: 10 * 60,
+            on_expiry: BudgetExpiryAction::Notify,
+        });
+        store.save_session(&session).unwrap();
+        store
+    }
+
+    #[test]
+    fn recovered_sessions_past_their_budget_dont_expire_again() {
+        let dir = TempDir::new();
+        let timer = TimerManager::new(budgeted_left_running(&dir, 61)).unwrap();
+        assert!(timer.check_budget().unwrap().is_none());
+    }
+
+    #[test]
+    fn recovered_sessions_past_their_warning_only_expire() {
+        let dir = TempDir::new();
+        let timer = TimerManager::new(budgeted_left_running(&dir, 55)).unwrap();
+        assert!(timer.check_budget().unwrap().is_none());
+
+        timer
+            .current_session
+            .lock()
+            .unwrap()
+            .as_mut()
+            .unwrap()
+            .start -= Duration::minutes(10);
+        let event = timer.check_budget().unwrap().unwrap();
+        assert_eq!(event.kind, BudgetEventKind::Expired);
+    }
+
+    #[test]
+    fn time_in_a_gap_hasnt_raised_budget_alerts() {
+        let dir = TempDir::new();
+        let store = budgeted_left_running(&dir, 0);
+        let mut session = store.get_active_sessions().unwrap().rows.remove(0);
+        session.start -= Duration::hours(3);
+        session.last_seen_at = Some(session.start + Duration::minutes(5));
+        store.save_session(&session).unwrap();
+
+        let timer = TimerManager::new(store).unwrap();
+        timer
+            .resolve_gap(&session.id, GapResolution::InsertBreak)
+            .unwrap();
+        assert!(timer.check_budget().unwrap().is_none());
+
+        timer
+            .current_session
+            .lock()
+            .unwrap()
+            .as_mut()
+            .unwrap()
+            .start -= Duration::minutes(50);
+        let event = timer.check_budget().unwrap().unwrap();
+        assert_eq!(event.kind, BudgetEventKind::Warning);
+    }
+
+    #[test]
+    fn every_recovered_timer_has_its_own_gap() {
+        let dir = TempDir::new();
+        let (store, main) = left_running(&dir, 3, 2);
+        let mut build = Session::new(None);
+        build.timer_name = "Build".to_string();
+        build.start -= Duration::hours(4);
+        build.last_seen_at = Some(Utc::now() - Duration::hours(2));
+        store.save_session(&build).unwrap();
+        store.save_primary_timer(DEFAULT_TIMER_NAME).unwrap();
+
+        let timer = TimerManager::new(store.clone()).unwrap();
+        let gaps: Vec<_> = timer
+            .get_pending_gaps()
+            .into_iter()
+            .map(|gap| (gap.session_id, gap.timer_name))
+            .collect();
+        assert_eq!(gaps.len(), 2);
+        assert!(gaps.contains(&(main.id.clone(), DEFAULT_TIMER_NAME.to_string())));
+        assert!(gaps.contains(&(build.id.clone(), "Build".to_string())));
+        assert!(timer.heartbeat().unwrap().is_empty());
+
+        // Settling the other timer's gap leaves the primary's pending
+        let ended = timer
+            .resolve_gap(&build.id, GapResolution::EndAtLastSeen)
+            .unwrap();
+        assert_eq!(ended.end, build.last_seen_at);
+        assert_eq!(timer.list_timers().len(), 1);
+        assert_eq!(timer.get_pending_gaps()[0].session_id, main.id);
+        assert!(timer.set_primary_timer("Build").is_err());
+    }
+
+    #[test]
+    fn heartbeat_finds_gaps_on_every_timer() {
+        let dir = TempDir::new();
+        let (_, timer) = fresh(&dir);
+        timer.start_session(None, None).unwrap();
+        let build = timer.start_named_session("Build", None, None).unwrap();
+        assert!(timer.heartbeat().unwrap().is_empty());
+
+        // The machine slept through the other timer's heartbeats
+        timer
+            .others
+            .lock()
+            .unwrap()
+            .get_mut("Build")
+            .unwrap()
+            .last_seen_at = Some(Utc::now() - Duration::hours(1));
+        let gaps = timer.heartbeat().unwrap();
+        assert_eq!(gaps.len(), 1);
+        assert_eq!(gaps[0].session_id, build.id);
+        assert!(timer.heartbeat().unwrap().is_empty());
+
+        timer
+            .resolve_gap(&build.id, GapResolution::InsertBreak)
+            .unwrap();
+        assert!(timer.get_pending_gaps().is_empty());
+    }
+
+    #[test]
+    fn older_sessions_left_open_on_a_timer_are_ended_on_recovery() {
+        let dir = TempDir::new();
+        let (store, older) = left_running(&dir, 30, 26);
+        let (_, newer) = left_running(&dir, 2, 0);
+
+        let timer = TimerManager::new(store.clone()).unwrap();
+        assert_eq!(timer.get_current_session().unwrap().id, newer.id);
+        assert_eq!(timer.list_timers().len(), 1);
+        assert!(timer.get_pending_gaps().is_empty());
+
+        let ended = store.get_session(&older.id).unwrap().unwrap();
+        assert_eq!(ended.end, older.last_seen_at);
+        assert!((ended.total_seconds - 4 * 3600).abs() <= 1);
+        assert_eq!(store.get_active_sessions().unwrap().rows.len(), 1);
+    }
+
+    #[test]
+    fn pending_gaps_survive_a_restart() {
+        let dir = TempDir::new();
+        let (store, session) = left_running(&dir, 3, 2);
+
+        let timer = TimerManager::new(store.clone()).unwrap();
+        assert_eq!(timer.get_pending_gaps().len(), 1);
+        assert!(timer.heartbeat().unwrap().is_empty());
+        let stored = store.get_session(&session.id).unwrap().unwrap();
+        assert_eq!(stored.last_seen_at, session.last_seen_at);
+        drop(timer);
+
+        // Quitting before deciding asks again on the next launch
+        let timer = TimerManager::new(store.clone()).unwrap();
+        let gap = timer.get_pending_gaps().remove(0);
+        assert_eq!(gap.last_seen_at, session.last_seen_at.unwrap());
+
+        // Once decided, heartbeats carry on without finding the gap again
+        timer.resolve_gap(&session.id, GapResolution::Keep).unwrap();
+        assert!(timer.heartbeat().unwrap().is_empty());
+        assert!(timer.get_pending_gaps().is_empty());
+        let stored = store.get_session(&session.id).unwrap().unwrap();
+        assert!(stored.last_seen_at > session.last_seen_at);
+    }
+
+    #[test]
+    fn tags_on_another_timer_survive_a_pause() {
+        let dir = TempDir::new();
+        let (store, timer) = fresh(&dir);
+        timer.start_session(None, None).unwrap();
+        let build = timer.start_named_session("Build", None, None).unwrap();
+
+        timer
+            .set_session_tags(&build.id, vec!["ci".to_string()])
+            .unwrap();
+        timer
+            .set_session_description(&build.id, Some("Nightly".to_string()))
+            .unwrap();
+        let paused = timer.pause_named_session("Build", None).unwrap();
+
+        assert_eq!(paused.tags, ["ci"]);
+        assert_eq!(paused.description.as_deref(), Some("Nightly"));
+        let stored = store.get_session(&build.id).unwrap().unwrap();
+        assert_eq!(stored.tags, ["ci"]);
+    }
+}
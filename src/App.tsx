@@ -4,48 +4,42 @@ import { MantineProvider, AppShell, Tabs, Container } from '@mantine/core';
 import { Notifications } from '@mantine/notifications';
 import { useEffect } from 'react';
 import { listen } from '@tauri-apps/api/event';
-import { useTimerStore } from './stores/timerStore';
+import { useTimerStore, TimerState, TimerEvent } from './stores/timerStore';
 import { TimerDisplay } from "./components/TimerDisplay";
 import { ControlButtons } from "./components/ControlButtons";
+import { TimersPanel } from "./components/TimersPanel";
 import { SummaryPage } from "./components/SummaryPage";
+import { IdlePrompt } from "./components/IdlePrompt";
+import { GapPrompt } from "./components/GapPrompt";
 
 function App() {
-  const { startTimer, pauseTimer, resumeTimer, endTimer } = useTimerStore();
+  const { fetchAllSessions } = useTimerStore();
 
   useEffect(() => {
-    console.log('Setting up tray event listeners...');
-    
-    const unlistenStart = listen('tray-start-timer', () => {
-      console.log('Received tray-start-timer event');
-      startTimer();
+    // The backend announces every timer change, whether it came from this
+    // window, the tray or the timer itself (idle, Pomodoro, budgets)
+    const unlistenState = listen<TimerState>('timer-state-changed', event => {
+      useTimerStore.setState({ timerState: event.payload });
     });
-
-    const unlistenPause = listen('tray-pause-timer', () => {
-      console.log('Received tray-pause-timer event');
-      pauseTimer();
-    });
-
-    const unlistenResume = listen('tray-resume-timer', () => {
-      console.log('Received tray-resume-timer event');
-      resumeTimer();
-    });
-
-    const unlistenEnd = listen('tray-end-timer', () => {
-      console.log('Received tray-end-timer event');
-      endTimer();
+    const unlistenEvents = listen<TimerEvent>('timer-event', event => {
+      if (event.payload.type === 'tick') {
+        useTimerStore.setState({ timerState: event.payload.state });
+      } else if (event.payload.type === 'ended') {
+        fetchAllSessions();
+      }
     });
 
     return () => {
-      unlistenStart.then(fn => fn());
-      unlistenPause.then(fn => fn());
-      unlistenResume.then(fn => fn());
-      unlistenEnd.then(fn => fn());
+      unlistenState.then(fn => fn());
+      unlistenEvents.then(fn => fn());
     };
-  }, [startTimer, pauseTimer, resumeTimer, endTimer]);
+  }, [fetchAllSessions]);
 
   return (
     <MantineProvider defaultColorScheme="light">
       <Notifications position="top-right" />
+      <IdlePrompt />
+      <GapPrompt />
       <AppShell padding="md">
         <Container size="sm" p={0}>
           <Tabs defaultValue="timer" variant="pills">
@@ -57,6 +51,7 @@ function App() {
             <Tabs.Panel value="timer" pt="md">
               <TimerDisplay />
               <ControlButtons />
+              <TimersPanel />
             </Tabs.Panel>
 
             <Tabs.Panel value="summary" pt="md">
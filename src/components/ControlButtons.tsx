@@ -1,13 +1,23 @@
-import { Group, Button } from '@mantine/core';
+import { useState } from 'react';
+import { Group, Button, NumberInput, Select, Menu } from '@mantine/core';
 import { notifications } from '@mantine/notifications';
-import { useTimerStore } from '../stores/timerStore';
+import { useTimerStore, BudgetExpiryAction, errorMessage } from '../stores/timerStore';
 
 export function ControlButtons() {
-  const { timerState, startTimer, pauseTimer, resumeTimer, endTimer } = useTimerStore();
+  const {
+    timerState, startTimer, pauseTimer, resumeTimer, endTimer, startPomodoro, stopPomodoro,
+  } = useTimerStore();
+  const [budgetMinutes, setBudgetMinutes] = useState<number | string>('');
+  const [onExpiry, setOnExpiry] = useState<BudgetExpiryAction>('notify');
 
   const handleStart = async () => {
     try {
-      await startTimer();
+      const seconds = Math.round(Number(budgetMinutes) * 60);
+      await startTimer(
+        seconds > 0
+          ? { seconds, warning_seconds: Math.min(300, Math.floor(seconds / 2)), on_expiry: onExpiry }
+          : undefined
+      );
       notifications.show({ 
         title: 'Timer Started', 
         message: 'Time tracking has begun!',
@@ -17,18 +27,18 @@ export function ControlButtons() {
       console.error('Failed to start:', error);
       notifications.show({ 
         title: 'Error', 
-        message: 'Failed to start timer',
+        message: errorMessage(error),
         color: 'red'
       });
     }
   };
 
-  const handlePause = async () => {
+  const handlePause = async (reason?: string) => {
     try {
-      await pauseTimer();
+      await pauseTimer(reason);
       notifications.show({ 
         title: 'Timer Paused', 
-        message: 'Time tracking paused',
+        message: reason ? `Time tracking paused (${reason})` : 'Time tracking paused',
         color: 'yellow'
       });
     } catch (error) {
@@ -62,8 +72,56 @@ export function ControlButtons() {
     }
   };
 
+  const handleStartPomodoro = async () => {
+    try {
+      await startPomodoro();
+      notifications.show({
+        title: 'Pomodoro Started',
+        message: 'Focus time!',
+        color: 'green'
+      });
+    } catch (error) {
+      console.error('Failed to start Pomodoro:', error);
+      notifications.show({
+        title: 'Error',
+        message: errorMessage(error),
+        color: 'red'
+      });
+    }
+  };
+
+  const handleStopPomodoro = async () => {
+    try {
+      await stopPomodoro();
+    } catch (error) {
+      console.error('Failed to stop Pomodoro:', error);
+    }
+  };
+
   return (
     <Group justify="center" gap="sm">
+      {!timerState.is_running && (
+        <Group grow style={{ width: '100%' }}>
+          <NumberInput
+            placeholder="Budget (minutes, optional)"
+            min={1}
+            value={budgetMinutes}
+            onChange={setBudgetMinutes}
+          />
+          <Select
+            data={[
+              { value: 'notify', label: 'Notify when up' },
+              { value: 'pause', label: 'Pause when up' },
+              { value: 'end', label: 'End when up' },
+            ]}
+            value={onExpiry}
+            onChange={value => setOnExpiry((value as BudgetExpiryAction) ?? 'notify')}
+            disabled={!Number(budgetMinutes)}
+            allowDeselect={false}
+          />
+        </Group>
+      )}
+
       {!timerState.is_running && (
         <Button 
           onClick={handleStart} 
@@ -75,16 +133,54 @@ export function ControlButtons() {
         </Button>
       )}
 
+      {!timerState.pomodoro && !(timerState.is_running && timerState.is_paused) && (
+        <Button
+          onClick={handleStartPomodoro}
+          color="grape"
+          variant="light"
+          size="lg"
+          fullWidth
+        >
+          Start Pomodoro
+        </Button>
+      )}
+
+      {timerState.pomodoro && (
+        <Button
+          onClick={handleStopPomodoro}
+          color="grape"
+          variant="light"
+          size="lg"
+          fullWidth
+        >
+          Stop Pomodoro
+        </Button>
+      )}
+
       {timerState.is_running && !timerState.is_paused && (
         <>
           <Button 
-            onClick={handlePause} 
+            onClick={() => handlePause()} 
             color="yellow" 
             size="lg"
             style={{ flex: 1 }}
           >
             Pause
           </Button>
+          <Menu position="bottom-end">
+            <Menu.Target>
+              <Button color="yellow" variant="light" size="lg">
+                Pause for…
+              </Button>
+            </Menu.Target>
+            <Menu.Dropdown>
+              {['Lunch', 'Meeting', 'Personal'].map(reason => (
+                <Menu.Item key={reason} onClick={() => handlePause(reason)}>
+                  {reason}
+                </Menu.Item>
+              ))}
+            </Menu.Dropdown>
+          </Menu>
           <Button 
             onClick={handleEnd} 
             color="red" 
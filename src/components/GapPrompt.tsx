@@ -0,0 +1,75 @@
+import { useEffect, useState } from 'react';
+import { Modal, Stack, Text, Group, Button } from '@mantine/core';
+import { notifications } from '@mantine/notifications';
+import { listen } from '@tauri-apps/api/event';
+import { invoke } from '@tauri-apps/api/core';
+import { useTimerStore, SessionGap, GapResolution } from '../stores/timerStore';
+
+export function GapPrompt() {
+  const { resolveGap } = useTimerStore();
+  // Gaps waiting on a decision, one timer at a time
+  const [gaps, setGaps] = useState<SessionGap[]>([]);
+  const gap = gaps[0] ?? null;
+
+  useEffect(() => {
+    const addGaps = (found: SessionGap[]) =>
+      setGaps(current => [
+        ...current,
+        ...found.filter(g => !current.some(c => c.session_id === g.session_id))
+      ]);
+
+    // Gaps found while recovering the sessions on startup
+    invoke<SessionGap[]>('get_pending_gaps').then(addGaps);
+
+    const unlistenGap = listen<SessionGap>('session-gap', event => {
+      addGaps([event.payload]);
+    });
+
+    return () => {
+      unlistenGap.then(fn => fn());
+    };
+  }, []);
+
+  const formatGap = () => {
+    if (!gap) return '';
+    const minutes = Math.round(
+      (new Date(gap.detected_at).getTime() - new Date(gap.last_seen_at).getTime()) / 60000
+    );
+    return minutes >= 120 ? `${Math.round(minutes / 60)} hours` : `${minutes} minutes`;
+  };
+
+  const handleResolve = async (resolution: GapResolution) => {
+    if (!gap) return;
+    try {
+      await resolveGap(gap.session_id, resolution);
+      setGaps(current => current.filter(g => g.session_id !== gap.session_id));
+    } catch (error) {
+      console.error('Failed to resolve session gap:', error);
+      notifications.show({
+        title: 'Error',
+        message: 'Could not apply your choice for the missing time',
+        color: 'red'
+      });
+    }
+  };
+
+  return (
+    <Modal opened={gap !== null} onClose={() => {}} withCloseButton={false} title="Timer was left running">
+      <Stack>
+        <Text>
+          The app wasn't running for {formatGap()} (since {gap && new Date(gap.last_seen_at).toLocaleString()}),
+          but your {gap?.timer_name} session was still open. What should happen to that time?
+        </Text>
+        <Group justify="flex-end">
+          <Button variant="default" onClick={() => handleResolve({ action: 'end_at_last_seen' })}>
+            End session then
+          </Button>
+          <Button variant="light" onClick={() => handleResolve({ action: 'insert_break' })}>
+            Record as break
+          </Button>
+          <Button onClick={() => handleResolve({ action: 'keep' })}>Keep as work</Button>
+        </Group>
+      </Stack>
+    </Modal>
+  );
+}
@@ -0,0 +1,77 @@
+import { useEffect, useState } from 'react';
+import { Modal, Stack, Text, Group, Button, TextInput } from '@mantine/core';
+import { notifications } from '@mantine/notifications';
+import { listen } from '@tauri-apps/api/event';
+import { invoke } from '@tauri-apps/api/core';
+import { useTimerStore, IdleInterval, IdleResolution } from '../stores/timerStore';
+
+export function IdlePrompt() {
+  const { resolveIdle } = useTimerStore();
+  const [idle, setIdle] = useState<IdleInterval | null>(null);
+  const [reason, setReason] = useState('');
+
+  useEffect(() => {
+    // Pick up a return that happened while the window was closed
+    invoke<IdleInterval | null>('get_pending_idle').then(pending => {
+      if (pending?.returned_at) setIdle(pending);
+    });
+
+    const unlistenReturned = listen<IdleInterval>('idle-returned', event => {
+      setIdle(event.payload);
+    });
+
+    return () => {
+      unlistenReturned.then(fn => fn());
+    };
+  }, []);
+
+  const idleMinutes = () => {
+    if (!idle) return 0;
+    const end = idle.returned_at ? new Date(idle.returned_at) : new Date();
+    return Math.round((end.getTime() - new Date(idle.started_at).getTime()) / 60000);
+  };
+
+  const handleResolve = async (resolution: IdleResolution) => {
+    try {
+      await resolveIdle(resolution);
+      setIdle(null);
+      setReason('');
+    } catch (error) {
+      console.error('Failed to resolve idle time:', error);
+      notifications.show({
+        title: 'Error',
+        message: 'Could not apply your choice for the idle time',
+        color: 'red'
+      });
+    }
+  };
+
+  return (
+    <Modal opened={idle !== null} onClose={() => {}} withCloseButton={false} title="Welcome back">
+      <Stack>
+        <Text>
+          You were away for {idleMinutes()} minutes, so the timer was paused. What should happen to that time?
+        </Text>
+        <TextInput
+          label="Reason (to record it as a break)"
+          placeholder="e.g. Meeting, Lunch"
+          value={reason}
+          onChange={e => setReason(e.currentTarget.value)}
+        />
+        <Group justify="flex-end">
+          <Button variant="default" onClick={() => handleResolve({ action: 'discard' })}>
+            Discard
+          </Button>
+          <Button
+            variant="light"
+            disabled={!reason.trim()}
+            onClick={() => handleResolve({ action: 'reassign', reason })}
+          >
+            Record as break
+          </Button>
+          <Button onClick={() => handleResolve({ action: 'keep' })}>Keep as work</Button>
+        </Group>
+      </Stack>
+    </Modal>
+  );
+}
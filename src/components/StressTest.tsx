@@ -1,7 +1,7 @@
 import { useState } from 'react';
 import { Stack, Button, Text, Group, Card, Badge, Progress, Alert } from '@mantine/core';
 import { notifications } from '@mantine/notifications';
-import { useTimerStore } from '../stores/timerStore';
+import { useTimerStore, errorMessage } from '../stores/timerStore';
 
 export function StressTest() {
   const { startTimer, pauseTimer, resumeTimer, endTimer, timerState } = useTimerStore();
@@ -108,7 +108,7 @@ export function StressTest() {
         notifications.show({ message: `✓ ${test.name}`, color: 'green' });
         await new Promise(r => setTimeout(r, 500));
       } catch (error: any) {
-        notifications.show({ message: `✗ ${test.name}: ${error}`, color: 'red' });
+        notifications.show({ message: `✗ ${test.name}: ${errorMessage(error)}`, color: 'red' });
       }
     }
 
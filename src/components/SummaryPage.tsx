@@ -1,10 +1,16 @@
 import { useEffect, useState } from 'react';
-import { Stack, Card, Text, Group, Select, Button, Table, Grid } from '@mantine/core';
+import { Stack, Card, Text, Group, Select, Button, Table, Grid, Alert } from '@mantine/core';
 import { notifications } from '@mantine/notifications';
 import { useTimerStore } from '../stores/timerStore';
 
 export function SummaryPage() {
-  const { monthlySummary, fetchMonthlySummary, exportMonthlySummary } = useTimerStore();
+  const {
+    monthlySummary,
+    skippedSessions,
+    fetchMonthlySummary,
+    fetchAllSessions,
+    exportMonthlySummary,
+  } = useTimerStore();
   const [selectedYear, setSelectedYear] = useState(new Date().getFullYear());
   const [selectedMonth, setSelectedMonth] = useState(new Date().getMonth() + 1);
   const [isExporting, setIsExporting] = useState(false);
@@ -13,6 +19,10 @@ export function SummaryPage() {
     fetchMonthlySummary(selectedYear, selectedMonth);
   }, [selectedYear, selectedMonth, fetchMonthlySummary]);
 
+  useEffect(() => {
+    fetchAllSessions();
+  }, [fetchAllSessions]);
+
   const formatDuration = (seconds: number) => {
     const hours = Math.floor(seconds / 3600);
     const minutes = Math.floor((seconds % 3600) / 60);
@@ -22,11 +32,13 @@ export function SummaryPage() {
   const handleExport = async () => {
     setIsExporting(true);
     try {
-      const path = await exportMonthlySummary(selectedYear, selectedMonth);
+      const { path, skipped } = await exportMonthlySummary(selectedYear, selectedMonth);
       notifications.show({
         title: 'Export Complete',
-        message: `Monthly summary exported to: ${path}`,
-        color: 'green'
+        message: skipped.length > 0
+          ? `Monthly summary exported to: ${path}. ${skipped.length} unreadable rows were left out; run the integrity check to find them.`
+          : `Monthly summary exported to: ${path}`,
+        color: skipped.length > 0 ? 'yellow' : 'green'
       });
     } catch (error) {
       console.error('Export failed:', error);
@@ -86,6 +98,12 @@ export function SummaryPage() {
         </Button>
       </Card>
 
+      {skippedSessions.length > 0 && (
+        <Alert color="yellow" title="Unreadable sessions">
+          {skippedSessions.length} sessions couldn't be read and are left out. Run the integrity check to find them.
+        </Alert>
+      )}
+
       {monthlySummary ? (
         <>
           <Grid>
@@ -127,7 +145,7 @@ export function SummaryPage() {
 
           {monthlySummary.weekly_breakdown && monthlySummary.weekly_breakdown.length > 0 && (
             <Card shadow="sm" padding="lg" radius="md" withBorder>
-              <Text size="lg" fw={600} mb="md">Weekly Breakdown (40h = Regular, &gt;40h = Overtime)</Text>
+              <Text size="lg" fw={600} mb="md">Weekly Breakdown</Text>
               <Table striped highlightOnHover>
                 <Table.Thead>
                   <Table.Tr>
@@ -141,7 +159,7 @@ export function SummaryPage() {
                 <Table.Tbody>
                   {monthlySummary.weekly_breakdown.map((week, idx) => (
                     <Table.Tr key={idx}>
-                      <Table.Td>{week.week_start}</Table.Td>
+                      <Table.Td>{week.week_start} – {week.week_end}</Table.Td>
                       <Table.Td>{week.session_count}</Table.Td>
                       <Table.Td fw={600}>{week.total_hours.toFixed(1)}h</Table.Td>
                       <Table.Td c="blue">{week.regular_hours.toFixed(1)}h</Table.Td>
@@ -171,7 +189,10 @@ export function SummaryPage() {
                     <Table.Tr key={day.date}>
                       <Table.Td>{day.date}</Table.Td>
                       <Table.Td>{day.session_count}</Table.Td>
-                      <Table.Td>{formatDuration(day.total_seconds)}</Table.Td>
+                      <Table.Td>
+                        {formatDuration(day.total_seconds)}
+                        {day.in_progress && <Text span c="green" size="sm"> (running)</Text>}
+                      </Table.Td>
                     </Table.Tr>
                   ))}
                 </Table.Tbody>
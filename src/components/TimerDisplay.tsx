@@ -5,13 +5,9 @@ import { useTimerStore } from '../stores/timerStore';
 export function TimerDisplay() {
   const { timerState, refreshTimerState } = useTimerStore();
 
+  // Load the state once; after that the backend's timer events keep it current
   useEffect(() => {
     refreshTimerState();
-    const interval = setInterval(() => {
-      refreshTimerState();
-    }, 1000);
-
-    return () => clearInterval(interval);
   }, [refreshTimerState]);
 
   const formatTime = (seconds: number) => {
@@ -31,6 +27,8 @@ export function TimerDisplay() {
     return timerState.is_paused ? 'Paused' : 'Active';
   };
 
+  const phaseLabels = { focus: 'Focus', short_break: 'Short break', long_break: 'Long break' };
+
   return (
     <Card shadow="sm" padding="lg" radius="md" withBorder mb="md">
       <Stack align="center" gap="md">
@@ -42,6 +40,13 @@ export function TimerDisplay() {
           {formatTime(timerState.elapsed_seconds)}
         </Text>
 
+        {timerState.pomodoro && (
+          <Text size="sm" c="dimmed">
+            {phaseLabels[timerState.pomodoro.phase]} {timerState.pomodoro.cycle}/{timerState.pomodoro.settings.cycles_before_long_break}
+            {' '}until {new Date(timerState.pomodoro.phase_ends_at).toLocaleTimeString([], { hour: '2-digit', minute: '2-digit' })}
+          </Text>
+        )}
+
         {timerState.current_session_id && (
           <Text size="xs" c="dimmed" ff="monospace">
             Session: {timerState.current_session_id.substring(0, 8)}...
@@ -0,0 +1,82 @@
+import { useState } from 'react';
+import { Card, Group, Stack, Text, TextInput, Button, Badge, ActionIcon } from '@mantine/core';
+import { notifications } from '@mantine/notifications';
+import { useTimerStore, RunningTimer, errorMessage } from '../stores/timerStore';
+
+const formatTime = (seconds: number) => {
+  const hours = Math.floor(seconds / 3600);
+  const minutes = Math.floor((seconds % 3600) / 60);
+  const secs = seconds % 60;
+  return `${hours}:${String(minutes).padStart(2, '0')}:${String(secs).padStart(2, '0')}`;
+};
+
+/** Timers running alongside the primary one, e.g. a long build or an on-call shift */
+export function TimersPanel() {
+  const {
+    timerState, startNamedTimer, pauseNamedTimer, resumeNamedTimer, endNamedTimer, setPrimaryTimer,
+  } = useTimerStore();
+  const [name, setName] = useState('');
+
+  const run = async (action: () => Promise<void>) => {
+    try {
+      await action();
+    } catch (error) {
+      notifications.show({ title: 'Error', message: errorMessage(error), color: 'red' });
+    }
+  };
+
+  const handleStart = () => run(async () => {
+    await startNamedTimer(name);
+    setName('');
+  });
+
+  const renderTimer = (timer: RunningTimer) => (
+    <Group key={timer.name} justify="space-between" wrap="nowrap">
+      <Group gap="xs">
+        <Text fw={500}>{timer.name}</Text>
+        {timer.primary && <Badge size="sm">Primary</Badge>}
+        {timer.is_paused && <Badge size="sm" color="yellow">Paused</Badge>}
+      </Group>
+      <Group gap="xs" wrap="nowrap">
+        <Text ff="monospace">{formatTime(timer.elapsed_seconds)}</Text>
+        {!timer.primary && (
+          <Button size="xs" variant="subtle" onClick={() => run(() => setPrimaryTimer(timer.name))}>
+            Make primary
+          </Button>
+        )}
+        {timer.is_paused ? (
+          <Button size="xs" variant="light" onClick={() => run(() => resumeNamedTimer(timer.name))}>
+            Resume
+          </Button>
+        ) : (
+          <Button size="xs" variant="light" color="yellow" onClick={() => run(() => pauseNamedTimer(timer.name))}>
+            Pause
+          </Button>
+        )}
+        <ActionIcon color="red" variant="light" onClick={() => run(() => endNamedTimer(timer.name))} title="End">
+          ■
+        </ActionIcon>
+      </Group>
+    </Group>
+  );
+
+  return (
+    <Card withBorder mt="md">
+      <Stack gap="xs">
+        <Text fw={600}>Timers</Text>
+        {timerState.timers.map(renderTimer)}
+        <Group wrap="nowrap">
+          <TextInput
+            placeholder="Timer name, e.g. Build"
+            value={name}
+            onChange={e => setName(e.currentTarget.value)}
+            style={{ flex: 1 }}
+          />
+          <Button onClick={handleStart} disabled={!name.trim()}>
+            Start timer
+          </Button>
+        </Group>
+      </Stack>
+    </Card>
+  );
+}
@@ -1,13 +1,139 @@
 import { create } from 'zustand';
 import { invoke } from '@tauri-apps/api/core';
 
+/** Error every backend command rejects with; `code` is stable, `message` is for people */
+export interface TimelyError {
+  code:
+    | 'not_found'
+    | 'already_active'
+    | 'already_paused'
+    | 'not_paused'
+    | 'validation'
+    | 'conflict'
+    | 'storage'
+    | 'export'
+    | 'internal';
+  message: string;
+}
+
+export function isTimelyError(error: unknown): error is TimelyError {
+  return typeof error === 'object' && error !== null && 'code' in error && 'message' in error;
+}
+
+/** Something to show the user for whatever a command rejected with */
+export function errorMessage(error: unknown): string {
+  return isTimelyError(error) ? error.message : String(error);
+}
+
 export interface Session {
   id: string;
   start: string;
-  pauses: string[];
-  resumes: string[];
+  breaks: Break[];
   end: string | null;
   total_seconds: number;
+  project_id: string | null;
+  description: string | null;
+  tags: string[];
+  last_seen_at: string | null;
+  budget: SessionBudget | null;
+  timer_name: string;
+}
+
+export type BudgetExpiryAction = 'notify' | 'pause' | 'end';
+
+export interface SessionBudget {
+  seconds: number;
+  warning_seconds: number;
+  on_expiry: BudgetExpiryAction;
+}
+
+export interface BudgetEvent {
+  kind: 'warning' | 'expired';
+  session_id: string;
+  budget_seconds: number;
+  elapsed_seconds: number;
+  action: BudgetExpiryAction;
+}
+
+export interface SessionQuery {
+  from?: string;
+  to?: string;
+  min_duration_seconds?: number;
+  max_duration_seconds?: number;
+  status?: 'open' | 'closed';
+  project_id?: string;
+  tags?: string[];
+  sort?: 'start_desc' | 'start_asc' | 'duration_desc' | 'duration_asc';
+  limit?: number;
+  offset?: number;
+}
+
+export interface SessionPage {
+  sessions: Session[];
+  total_count: number;
+  next_offset: number | null;
+  skipped: SkippedRow[];
+}
+
+/** A stored row left out of a list because it couldn't be read */
+export interface SkippedRow {
+  table: string;
+  id: string;
+  error: string;
+}
+
+/** The rows of a list that could be read, and the ones left out */
+export interface Rows<T> {
+  rows: T[];
+  skipped: SkippedRow[];
+}
+
+/** Where a monthly export was written, and the rows it had to leave out */
+export interface MonthlyExport {
+  path: string;
+  skipped: SkippedRow[];
+}
+
+export type IntegrityIssueKind =
+  | 'unparsable_timestamp'
+  | 'mismatched_break'
+  | 'negative_duration'
+  | 'stale_total'
+  | 'unreadable_legacy_break';
+
+export interface IntegrityIssue {
+  kind: IntegrityIssueKind;
+  table: string;
+  id: string;
+  message: string;
+  fixed: boolean;
+}
+
+export interface IntegrityReport {
+  sessions_checked: number;
+  breaks_checked: number;
+  issues: IntegrityIssue[];
+}
+
+export interface Break {
+  id: string;
+  session_id: string;
+  started_at: string;
+  ended_at: string | null;
+  reason: string | null;
+}
+
+export interface Client {
+  id: string;
+  name: string;
+  created_at: string;
+}
+
+export interface Project {
+  id: string;
+  name: string;
+  client_id: string | null;
+  created_at: string;
 }
 
 export interface TimerState {
@@ -15,6 +141,42 @@ export interface TimerState {
   is_paused: boolean;
   current_session_id: string | null;
   elapsed_seconds: number;
+  pomodoro: PomodoroState | null;
+  timers: RunningTimer[];
+}
+
+export interface RunningTimer {
+  name: string;
+  primary: boolean;
+  session_id: string;
+  project_id: string | null;
+  is_paused: boolean;
+  elapsed_seconds: number;
+}
+
+export type PomodoroPhase = 'focus' | 'short_break' | 'long_break';
+
+export interface PomodoroSettings {
+  focus_minutes: number;
+  short_break_minutes: number;
+  long_break_minutes: number;
+  cycles_before_long_break: number;
+}
+
+export interface PomodoroState {
+  phase: PomodoroPhase;
+  cycle: number;
+  phase_started_at: string;
+  phase_ends_at: string;
+  settings: PomodoroSettings;
+}
+
+export interface WorkRules {
+  weekly_threshold_hours: number;
+  daily_threshold_hours: number | null;
+  week_start: 'Mon' | 'Tue' | 'Wed' | 'Thu' | 'Fri' | 'Sat' | 'Sun';
+  weekly_overtime_multiplier: number;
+  daily_overtime_multiplier: number;
 }
 
 export interface WeeklySummary {
@@ -22,7 +184,69 @@ export interface WeeklySummary {
   week_end: string;
   regular_hours: number;
   overtime_hours: number;
+  weighted_hours: number;
   total_hours: number;
+  in_range_hours: number;
+  session_count: number;
+  days: WeekDaySummary[];
+}
+
+export interface WeekDaySummary {
+  date: string;
+  total_seconds: number;
+  in_range: boolean;
+}
+
+export type SummaryRange =
+  | { kind: 'day'; date: string }
+  | { kind: 'week'; year: number; week: number }
+  | { kind: 'month'; year: number; month: number }
+  | { kind: 'quarter'; year: number; quarter: number }
+  | { kind: 'year'; year: number }
+  | { kind: 'custom'; start: string; end: string };
+
+export type Granularity = 'day' | 'week' | 'month' | 'quarter' | 'year';
+
+export interface PeriodSummary {
+  start: string;
+  end: string;
+  total_seconds: number;
+  session_count: number;
+  in_progress: boolean;
+}
+
+export interface Summary {
+  start: string;
+  end: string;
+  granularity: Granularity;
+  total_seconds: number;
+  break_seconds: number;
+  regular_hours: number;
+  overtime_hours: number;
+  weighted_hours: number;
+  session_count: number;
+  longest_session_seconds: number;
+  breakdown: PeriodSummary[];
+  weekly_breakdown: WeeklySummary[];
+  project_breakdown: ProjectSummary[];
+  overlap: OverlapMode;
+  timer_breakdown: TimerSummary[];
+  /** Rows left out of the figures because they couldn't be read */
+  skipped: SkippedRow[];
+}
+
+/** A change to the timers, sent by the backend as the `timer-event` event */
+export type TimerEvent =
+  | { type: 'started' | 'paused' | 'resumed' | 'ended'; session: Session }
+  | { type: 'changed' }
+  | { type: 'tick'; state: TimerState };
+
+/** Whether time on concurrent timers counts once or once per timer */
+export type OverlapMode = 'per_timer' | 'once';
+
+export interface TimerSummary {
+  timer_name: string;
+  total_seconds: number;
   session_count: number;
 }
 
@@ -30,37 +254,109 @@ export interface MonthlySummary {
   year: number;
   month: number;
   total_seconds: number;
+  break_seconds: number;
   regular_hours: number;
   overtime_hours: number;
+  weighted_hours: number;
   session_count: number;
   longest_session_seconds: number;
   daily_breakdown: DailySummary[];
   weekly_breakdown: WeeklySummary[];
+  project_breakdown: ProjectSummary[];
+  /** Rows left out of the figures because they couldn't be read */
+  skipped: SkippedRow[];
+}
+
+export interface ProjectSummary {
+  project_id: string | null;
+  project_name: string | null;
+  client_id: string | null;
+  client_name: string | null;
+  total_seconds: number;
+  session_count: number;
 }
 
 export interface DailySummary {
   date: string;
   total_seconds: number;
   session_count: number;
+  in_progress: boolean;
+}
+
+export interface IdleSettings {
+  enabled: boolean;
+  threshold_minutes: number;
+  pause_on_lock: boolean;
+}
+
+export interface IdleInterval {
+  session_id: string;
+  break_id: string;
+  started_at: string;
+  returned_at: string | null;
+}
+
+export type IdleResolution =
+  | { action: 'keep' }
+  | { action: 'discard' }
+  | { action: 'reassign'; reason: string };
+
+export interface SessionGap {
+  session_id: string;
+  timer_name: string;
+  last_seen_at: string;
+  detected_at: string;
+}
+
+export type GapResolution =
+  | { action: 'end_at_last_seen' }
+  | { action: 'insert_break' }
+  | { action: 'keep' };
+
+export type HotkeyAction = 'start' | 'pause' | 'resume' | 'end';
+
+export interface HotkeySettings {
+  enabled: boolean;
+  start: string | null;
+  pause: string | null;
+  resume: string | null;
+  end: string | null;
+}
+
+export interface HotkeyConflict {
+  action: HotkeyAction;
+  shortcut: string;
+  message: string;
 }
 
 interface TimerStore {
   timerState: TimerState;
   currentTime: number;
   sessions: Session[];
+  /** Sessions left out of `sessions` because they couldn't be read */
+  skippedSessions: SkippedRow[];
   monthlySummary: MonthlySummary | null;
   
   // Actions
-  startTimer: () => Promise<void>;
-  pauseTimer: () => Promise<void>;
+  startTimer: (budget?: SessionBudget) => Promise<void>;
+  pauseTimer: (reason?: string) => Promise<void>;
   resumeTimer: () => Promise<void>;
   endTimer: () => Promise<void>;
+  startPomodoro: () => Promise<void>;
+  stopPomodoro: () => Promise<void>;
   refreshTimerState: () => Promise<void>;
-  updateTray: () => Promise<void>;
+  startNamedTimer: (name: string) => Promise<void>;
+  pauseNamedTimer: (name: string) => Promise<void>;
+  resumeNamedTimer: (name: string) => Promise<void>;
+  endNamedTimer: (name: string) => Promise<void>;
+  setPrimaryTimer: (name: string) => Promise<void>;
   fetchAllSessions: () => Promise<void>;
   fetchMonthlySummary: (year: number, month: number) => Promise<void>;
   exportSession: (session: Session) => Promise<string>;
-  exportMonthlySummary: (year: number, month: number) => Promise<string>;
+  exportMonthlySummary: (year: number, month: number) => Promise<MonthlyExport>;
+  resolveIdle: (resolution: IdleResolution) => Promise<void>;
+  resolveGap: (sessionId: string, resolution: GapResolution) => Promise<void>;
+  checkIntegrity: (fix?: boolean) => Promise<IntegrityReport>;
   tick: () => void;
 }
 
@@ -70,47 +366,28 @@ export const useTimerStore = create<TimerStore>((set, get) => ({
     is_paused: false,
     current_session_id: null,
     elapsed_seconds: 0,
+    pomodoro: null,
+    timers: [],
   },
   currentTime: 0,
   sessions: [],
+  skippedSessions: [],
   monthlySummary: null,
 
-  startTimer: async () => {
+  startTimer: async (budget?: SessionBudget) => {
     try {
       console.log('Starting timer...');
-      const result = await invoke('start_timer');
+      const result = await invoke('start_timer', { budget: budget ?? null });
       console.log('Timer started:', result);
-      
-      // Refresh state and get it directly
-      const state = await invoke<TimerState>('get_timer_state');
-      set({ timerState: state });
-      
-      // Update tray with the fresh state
-      await invoke('update_tray', {
-        isRunning: state.is_running,
-        isPaused: state.is_paused,
-        elapsedSeconds: state.elapsed_seconds,
-      });
     } catch (error) {
       console.error('Failed to start timer:', error);
       throw error;
     }
   },
 
-  pauseTimer: async () => {
+  pauseTimer: async (reason?: string) => {
     try {
-      await invoke('pause_timer');
-      
-      // Refresh state and get it directly
-      const state = await invoke<TimerState>('get_timer_state');
-      set({ timerState: state });
-      
-      // Update tray with the fresh state
-      await invoke('update_tray', {
-        isRunning: state.is_running,
-        isPaused: state.is_paused,
-        elapsedSeconds: state.elapsed_seconds,
-      });
+      await invoke('pause_timer', { reason: reason ?? null });
     } catch (error) {
       console.error('Failed to pause timer:', error);
       throw error;
@@ -120,17 +397,6 @@ export const useTimerStore = create<TimerStore>((set, get) => ({
   resumeTimer: async () => {
     try {
       await invoke('resume_timer');
-      
-      // Refresh state and get it directly
-      const state = await invoke<TimerState>('get_timer_state');
-      set({ timerState: state });
-      
-      // Update tray with the fresh state
-      await invoke('update_tray', {
-        isRunning: state.is_running,
-        isPaused: state.is_paused,
-        elapsedSeconds: state.elapsed_seconds,
-      });
     } catch (error) {
       console.error('Failed to resume timer:', error);
       throw error;
@@ -141,19 +407,6 @@ export const useTimerStore = create<TimerStore>((set, get) => ({
     try {
       const session = await invoke<Session>('end_timer');
       
-      // Refresh state and get it directly
-      const state = await invoke<TimerState>('get_timer_state');
-      set({ timerState: state });
-      
-      // Update tray with the fresh state
-      await invoke('update_tray', {
-        isRunning: state.is_running,
-        isPaused: state.is_paused,
-        elapsedSeconds: state.elapsed_seconds,
-      });
-      
-      await get().fetchAllSessions();
-      
       // Auto-export session
       try {
         const exportPath = await get().exportSession(session);
@@ -167,6 +420,24 @@ export const useTimerStore = create<TimerStore>((set, get) => ({
     }
   },
 
+  startPomodoro: async () => {
+    try {
+      await invoke('start_pomodoro');
+    } catch (error) {
+      console.error('Failed to start Pomodoro:', error);
+      throw error;
+    }
+  },
+
+  stopPomodoro: async () => {
+    try {
+      await invoke('stop_pomodoro');
+    } catch (error) {
+      console.error('Failed to stop Pomodoro:', error);
+      throw error;
+    }
+  },
+
   refreshTimerState: async () => {
     try {
       console.log('Refreshing timer state...');
@@ -179,25 +450,30 @@ export const useTimerStore = create<TimerStore>((set, get) => ({
     }
   },
 
-  updateTray: async () => {
+  resolveIdle: async (resolution: IdleResolution) => {
     try {
-      const state = get().timerState;
-      console.log('Updating tray with state:', state);
-      await invoke('update_tray', {
-        isRunning: state.is_running,
-        isPaused: state.is_paused,
-        elapsedSeconds: state.elapsed_seconds,
-      });
-      console.log('Tray updated successfully');
+      await invoke('resolve_idle', { resolution });
     } catch (error) {
-      console.error('Failed to update tray:', error);
+      console.error('Failed to resolve idle time:', error);
+      throw error;
+    }
+  },
+
+  resolveGap: async (sessionId: string, resolution: GapResolution) => {
+    try {
+      await invoke('resolve_gap', { sessionId, resolution });
+
+      await get().fetchAllSessions();
+    } catch (error) {
+      console.error('Failed to resolve session gap:', error);
+      throw error;
     }
   },
 
   fetchAllSessions: async () => {
     try {
-      const sessions = await invoke<Session[]>('get_all_sessions');
-      set({ sessions });
+      const { rows, skipped } = await invoke<Rows<Session>>('get_all_sessions');
+      set({ sessions: rows, skippedSessions: skipped });
     } catch (error) {
       console.error('Failed to fetch sessions:', error);
     }
@@ -224,14 +500,41 @@ export const useTimerStore = create<TimerStore>((set, get) => ({
 
   exportMonthlySummary: async (year: number, month: number) => {
     try {
-      const path = await invoke<string>('export_monthly_summary', { year, month });
-      return path;
+      return await invoke<MonthlyExport>('export_monthly_summary', { year, month });
     } catch (error) {
       console.error('Failed to export monthly summary:', error);
       throw error;
     }
   },
 
+  checkIntegrity: async (fix?: boolean) => {
+    const report = await invoke<IntegrityReport>('check_integrity', { fix: fix ?? false });
+    if (report.issues.some(issue => issue.fixed)) {
+      await get().fetchAllSessions();
+    }
+    return report;
+  },
+
+  startNamedTimer: async (name: string) => {
+    await invoke('start_named_timer', { name });
+  },
+
+  pauseNamedTimer: async (name: string) => {
+    await invoke('pause_named_timer', { name });
+  },
+
+  resumeNamedTimer: async (name: string) => {
+    await invoke('resume_named_timer', { name });
+  },
+
+  endNamedTimer: async (name: string) => {
+    await invoke('end_named_timer', { name });
+  },
+
+  setPrimaryTimer: async (name: string) => {
+    await invoke('set_primary_timer', { name });
+  },
+
   tick: () => {
     set((state) => ({
       currentTime: state.currentTime + 1,